@@ -0,0 +1,206 @@
+//! Changelog module for nbr
+//!
+//! Describes a pending plugin update: the version jump, the changelog link from the package
+//! metadata and the release notes of plugins hosted on GitHub.
+use crate::dist::Distribution;
+use crate::log::StyledText;
+use crate::uv::Package;
+use crate::version;
+use anyhow::{Context, Result};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::debug;
+
+/// `Project-URL` labels pointing to a changelog
+const CHANGELOG_LABELS: [&str; 6] = [
+    "changelog",
+    "changes",
+    "release-notes",
+    "releases",
+    "history",
+    "news",
+];
+/// `Project-URL` labels pointing to the source repository
+const REPOSITORY_LABELS: [&str; 4] = ["repository", "source", "source-code", "homepage"];
+/// Lines of release notes shown before truncating
+const MAX_NOTES_LINES: usize = 8;
+
+#[derive(Debug, Deserialize)]
+struct GithubRelease {
+    html_url: String,
+    body: Option<String>,
+}
+
+/// Release notes of the target version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseNotes {
+    pub url: String,
+    pub body: String,
+}
+
+/// What an update of an outdated package brings
+#[derive(Debug, Clone)]
+pub struct UpdatePreview {
+    pub package: Package,
+    pub target_version: String,
+    pub crosses_major: bool,
+    pub changelog_url: Option<String>,
+    pub release_notes: Option<ReleaseNotes>,
+}
+
+impl UpdatePreview {
+    /// Collect the preview of `package`, `homepage` is the registry homepage if known
+    ///
+    /// Release notes are best effort, a failed request only leaves them empty.
+    pub async fn collect(
+        client: &Client,
+        work_dir: &Path,
+        package: &Package,
+        homepage: Option<&str>,
+    ) -> Self {
+        let target_version = package.latest_version.clone().unwrap_or_default();
+        let distribution = Distribution::find(work_dir, &package.name);
+        let changelog_url = distribution
+            .as_ref()
+            .and_then(|d| d.project_url(&CHANGELOG_LABELS));
+        let repository = homepage.and_then(github_repo).or_else(|| {
+            let url = distribution.as_ref()?.project_url(&REPOSITORY_LABELS)?;
+            github_repo(&url)
+        });
+
+        let release_notes = match repository {
+            Some((owner, repo)) => {
+                match fetch_release_notes(client, &owner, &repo, &target_version).await {
+                    Ok(notes) => notes,
+                    Err(e) => {
+                        debug!("Failed to fetch release notes of {}: {:#}", package.name, e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        Self {
+            package: package.clone(),
+            crosses_major: version::crosses_major(&package.version, &target_version),
+            target_version,
+            changelog_url,
+            release_notes,
+        }
+    }
+
+    /// `name current → target`, used as the selection label
+    pub fn summary(&self) -> String {
+        format!(
+            "{} {} → {}",
+            self.package.name, self.package.version, self.target_version
+        )
+    }
+
+    pub fn display(&self) {
+        StyledText::new(" ")
+            .text(" ")
+            .cyan_bold(&self.package.name)
+            .green(format!("v{}", self.package.version))
+            .text("→")
+            .yellow_bold(format!("v{}", self.target_version))
+            .with(|text| {
+                if self.crosses_major {
+                    text.red_bold("(major update)");
+                }
+            })
+            .println();
+        if let Some(ref url) = self.changelog_url {
+            StyledText::new(" ")
+                .text("   ")
+                .blue("↳ Changelog:")
+                .white(url)
+                .println();
+        }
+        if let Some(ref notes) = self.release_notes {
+            StyledText::new(" ")
+                .text("   ")
+                .blue("↳ Release notes:")
+                .white(&notes.url)
+                .println();
+            let lines: Vec<&str> = notes
+                .body
+                .lines()
+                .map(str::trim_end)
+                .filter(|line| !line.is_empty())
+                .collect();
+            for line in lines.iter().take(MAX_NOTES_LINES) {
+                println!("      {line}");
+            }
+            if lines.len() > MAX_NOTES_LINES {
+                println!("      ...");
+            }
+        }
+    }
+}
+
+/// Owner and name of a `https://github.com/<owner>/<repo>` url
+pub fn github_repo(url: &str) -> Option<(String, String)> {
+    let path = url
+        .trim()
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.trim().strip_prefix("http://github.com/"))?;
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    let repo = repo.strip_suffix(".git").unwrap_or(repo);
+    Some((owner.to_string(), repo.to_string()))
+}
+
+/// Release of `version` on GitHub, tagged `v<version>` or `<version>`
+async fn fetch_release_notes(
+    client: &Client,
+    owner: &str,
+    repo: &str,
+    version: &str,
+) -> Result<Option<ReleaseNotes>> {
+    for tag in [format!("v{version}"), version.to_string()] {
+        let url = format!("https://api.github.com/repos/{owner}/{repo}/releases/tags/{tag}");
+        let response = client
+            .get(&url)
+            .header("Accept", "application/vnd.github+json")
+            .send()
+            .await
+            .with_context(|| format!("Network error while fetching {}", url))?;
+        if response.status() == StatusCode::NOT_FOUND {
+            continue;
+        }
+        let release: GithubRelease = response
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch {}", url))?
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {}", url))?;
+        return Ok(Some(ReleaseNotes {
+            url: release.html_url,
+            body: release.body.unwrap_or_default(),
+        }));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_github_repo() {
+        assert_eq!(
+            github_repo("https://github.com/nonebot/plugin-status/"),
+            Some(("nonebot".to_string(), "plugin-status".to_string()))
+        );
+        assert_eq!(
+            github_repo("https://github.com/owner/nonebot-plugin-abs.git"),
+            Some(("owner".to_string(), "nonebot-plugin-abs".to_string()))
+        );
+        assert!(github_repo("https://github.com/owner").is_none());
+        assert!(github_repo("https://gitlab.com/owner/repo").is_none());
+    }
+}
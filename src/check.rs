@@ -0,0 +1,561 @@
+//! Plugin project check module for nbr
+//!
+//! Checks a plugin package against the NoneBot registry publishing rules before it is
+//! submitted, every rule produces a pass / fail [`CheckItem`].
+use crate::cli::env::find_python_executable;
+use crate::dist;
+use crate::log::StyledText;
+use crate::utils::fs_utils::TempDir;
+use crate::uv::CmdBuilder;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use strum::Display;
+
+/// Plugin types accepted by the registry
+pub const PLUGIN_TYPES: [&str; 2] = ["application", "library"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+/// Result of one rule, named after the registry field it checks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckItem {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl CheckItem {
+    pub fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn pass(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, message)
+    }
+
+    fn fail(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, message)
+    }
+
+    fn skip(name: &str, message: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, message)
+    }
+
+    pub fn display(&self) {
+        StyledText::new(" ")
+            .text(" ")
+            .with(|text| match self.status {
+                CheckStatus::Pass => {
+                    text.green_bold("✓");
+                }
+                CheckStatus::Fail => {
+                    text.red_bold("✗");
+                }
+                CheckStatus::Skip => {
+                    text.yellow_bold("-");
+                }
+            })
+            .cyan(&self.name)
+            .text(&self.message)
+            .println();
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PluginPyProject {
+    project: Option<ProjectTable>,
+}
+
+/// `[project]` of a plugin package
+///
+/// Unlike [`crate::pyproject::Project`] every field is optional, missing fields are
+/// reported by the checks instead of failing the parse.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectTable {
+    pub name: Option<String>,
+    pub version: Option<String>,
+    #[serde(default)]
+    pub dynamic: Vec<String>,
+    pub description: Option<String>,
+    pub requires_python: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub urls: HashMap<String, String>,
+}
+
+impl ProjectTable {
+    /// Required fields and whether they are set, a `dynamic` field counts as set
+    fn fields(&self) -> [(&'static str, bool); 4] {
+        let is_set = |value: &Option<String>, field: &str| {
+            value.as_deref().is_some_and(|v| !v.trim().is_empty())
+                || self.dynamic.iter().any(|d| d == field)
+        };
+        [
+            ("name", is_set(&self.name, "name")),
+            ("version", is_set(&self.version, "version")),
+            ("description", is_set(&self.description, "description")),
+            (
+                "requires-python",
+                is_set(&self.requires_python, "requires-python"),
+            ),
+        ]
+    }
+}
+
+/// A plugin package in development
+#[derive(Debug, Clone)]
+pub struct PluginProject {
+    pub dir: PathBuf,
+    /// `project.name`, the registry `project_link`
+    pub project_link: String,
+    /// The registry `module_name`
+    pub module_name: String,
+    pub project: ProjectTable,
+    /// The package directory or single module file, `None` if not found
+    pub module_path: Option<PathBuf>,
+}
+
+impl PluginProject {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let toml_path = dir.join("pyproject.toml");
+        let content = fs::read_to_string(&toml_path)
+            .with_context(|| format!("Failed to read {}", toml_path.display()))?;
+        let project = toml::from_str::<PluginPyProject>(&content)
+            .with_context(|| format!("Failed to parse {}", toml_path.display()))?
+            .project
+            .unwrap_or_default();
+        let project_link = project.name.clone().unwrap_or_default();
+        let module_name = project_link.replace("-", "_");
+        let module_path = [dir.join("src"), dir.to_path_buf()]
+            .iter()
+            .flat_map(|root| {
+                [
+                    root.join(&module_name),
+                    root.join(format!("{module_name}.py")),
+                ]
+            })
+            .find(|path| path.join("__init__.py").is_file() || path.is_file());
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            project_link,
+            module_name,
+            project,
+            module_path,
+        })
+    }
+
+    /// `__plugin_meta__` of the module, `None` if it is not defined
+    pub fn plugin_meta(&self) -> Option<PluginMeta> {
+        self.init_source()
+            .and_then(|source| plugin_meta_args(&source))
+            .map(PluginMeta)
+    }
+
+    /// Source of the module defining `__plugin_meta__`
+    fn init_source(&self) -> Option<String> {
+        let path = self.module_path.as_ref()?;
+        let init = if path.is_dir() {
+            path.join("__init__.py")
+        } else {
+            path.clone()
+        };
+        fs::read_to_string(init).ok()
+    }
+
+    /// All python sources of the module
+    fn sources(&self) -> Vec<String> {
+        let Some(ref path) = self.module_path else {
+            return Vec::new();
+        };
+        let mut sources = Vec::new();
+        let mut stack = vec![path.clone()];
+        while let Some(path) = stack.pop() {
+            if path.is_dir() {
+                if let Ok(entries) = fs::read_dir(&path) {
+                    stack.extend(entries.flatten().map(|entry| entry.path()));
+                }
+            } else if path.extension().is_some_and(|ext| ext == "py")
+                && let Ok(source) = fs::read_to_string(&path)
+            {
+                sources.push(source);
+            }
+        }
+        sources
+    }
+
+    /// Naming, metadata and dependency rules, checked from the sources
+    pub fn check_static(&self) -> Vec<CheckItem> {
+        let mut items: Vec<CheckItem> = self
+            .project
+            .fields()
+            .into_iter()
+            .map(|(field, is_set)| {
+                let name = format!("project.{field}");
+                if is_set {
+                    CheckItem::pass(&name, "Set in pyproject.toml")
+                } else {
+                    CheckItem::fail(&name, format!("{name} is not set in pyproject.toml"))
+                }
+            })
+            .collect();
+
+        if self.project_link.starts_with("nonebot-plugin-") {
+            items.push(CheckItem::pass("project_link", &self.project_link));
+        } else {
+            items.push(CheckItem::fail(
+                "project_link",
+                format!("'{}' should start with nonebot-plugin-", self.project_link),
+            ));
+        }
+        match self.module_path {
+            Some(_) => items.push(CheckItem::pass("module_name", &self.module_name)),
+            None => items.push(CheckItem::fail(
+                "module_name",
+                format!(
+                    "Module '{}' not found in {} or src/",
+                    self.module_name,
+                    self.dir.display()
+                ),
+            )),
+        }
+
+        let Some(meta) = self.plugin_meta() else {
+            items.push(CheckItem::fail(
+                "__plugin_meta__",
+                "PluginMetadata is not defined in the module",
+            ));
+            return items;
+        };
+        items.push(CheckItem::pass("__plugin_meta__", "PluginMetadata found"));
+
+        items.push(match meta.value("type") {
+            Some(value)
+                if meta
+                    .string("type")
+                    .is_some_and(|t| PLUGIN_TYPES.contains(&t)) =>
+            {
+                CheckItem::pass("type", value)
+            }
+            Some(value) => CheckItem::fail(
+                "type",
+                format!("{} is not one of {}", value, PLUGIN_TYPES.join(", ")),
+            ),
+            None => CheckItem::fail("type", "type is not set in PluginMetadata"),
+        });
+        items.push(match meta.value("homepage") {
+            Some(value) if value != "None" => CheckItem::pass("homepage", value),
+            _ => CheckItem::fail("homepage", "homepage is not set in PluginMetadata"),
+        });
+        items.push(match meta.value("supported_adapters") {
+            Some(value) => CheckItem::pass("supported_adapters", value),
+            None => CheckItem::fail(
+                "supported_adapters",
+                "supported_adapters is not set in PluginMetadata, use None for all adapters",
+            ),
+        });
+
+        items.push(self.check_required_plugins());
+        items
+    }
+
+    /// Plugins loaded with `require` must be declared in `project.dependencies`
+    fn check_required_plugins(&self) -> CheckItem {
+        let Ok(re) = Regex::new(r#"\brequire(?:_plugin)?\(\s*["']([\w.]+)["']\s*\)"#) else {
+            return CheckItem::skip("dependencies", "Invalid regex pattern");
+        };
+        let declared: Vec<String> = self
+            .project
+            .dependencies
+            .iter()
+            .map(|dependency| dist::normalize_name(dist::requirement_name(dependency)))
+            .collect();
+        let mut missing: Vec<String> = Vec::new();
+        for source in self.sources() {
+            for captures in re.captures_iter(&source) {
+                let module = &captures[1];
+                // nonebot 自带的插件无需声明依赖
+                if module == "nonebot" || module.starts_with("nonebot.") {
+                    continue;
+                }
+                // 依赖插件的包名与模块名一致，子模块以顶层模块为准
+                let package = module.split('.').next().unwrap_or(module);
+                if !declared.contains(&dist::normalize_name(package))
+                    && !missing.iter().any(|m| m == package)
+                {
+                    missing.push(package.to_string());
+                }
+            }
+        }
+        if missing.is_empty() {
+            CheckItem::pass(
+                "dependencies",
+                "Required plugins are declared in project.dependencies",
+            )
+        } else {
+            missing.sort();
+            CheckItem::fail(
+                "dependencies",
+                format!(
+                    "Required but not in project.dependencies: {}",
+                    missing.join(", ")
+                ),
+            )
+        }
+    }
+
+    /// Install the package into a fresh venv and load it with nonebot
+    pub async fn check_import(&self) -> CheckItem {
+        match self.import_in_isolated_venv().await {
+            Ok(()) => CheckItem::pass("import", "Loaded in an isolated venv"),
+            Err(e) => CheckItem::fail("import", format!("{:#}", e)),
+        }
+    }
+
+    async fn import_in_isolated_venv(&self) -> Result<()> {
+        let venv_dir = TempDir::new("check")?;
+        let project_dir = self
+            .dir
+            .canonicalize()
+            .context("Invalid project directory")?;
+        let project_dir = project_dir.to_string_lossy();
+
+        CmdBuilder::uv(vec!["venv", "--quiet", ".venv"])
+            .working_dir(venv_dir.path())
+            .timeout(120)
+            .run_async()
+            .await?;
+        let python = find_python_executable(venv_dir.path())?;
+        CmdBuilder::uv(vec![
+            "pip",
+            "install",
+            "--quiet",
+            "--python",
+            &python,
+            &project_dir,
+        ])
+        .working_dir(venv_dir.path())
+        .timeout(600)
+        .run_async()
+        .await?;
+
+        let script = format!(
+            "import nonebot\nnonebot.init(driver='~none')\nassert nonebot.load_plugin('{}') is not None",
+            self.module_name
+        );
+        crate::utils::process_utils::execute_command_with_output(
+            &python,
+            &["-c", &script],
+            Some(venv_dir.path()),
+            120,
+        )
+        .await
+        .context("Failed to load the plugin")?;
+        Ok(())
+    }
+}
+
+/// Arguments of `__plugin_meta__ = PluginMetadata(...)`
+#[derive(Debug, Clone)]
+pub struct PluginMeta(String);
+
+impl PluginMeta {
+    /// Source of a keyword argument, e.g. `{"~onebot.v11"}` of `supported_adapters`
+    pub fn value(&self, keyword: &str) -> Option<&str> {
+        let re = Regex::new(&format!(r"\b{keyword}\s*=\s*")).ok()?;
+        let start = re.find(&self.0)?.end();
+        let rest = &self.0[start..];
+        let mut depth = 0;
+        let mut quote = None;
+        let mut end = rest.len();
+        for (i, c) in rest.char_indices() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => {}
+                (None, '"' | '\'') => quote = Some(c),
+                (None, '(' | '[' | '{') => depth += 1,
+                (None, ')' | ']' | '}') => depth -= 1,
+                (None, ',') if depth == 0 => {
+                    end = i;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        Some(rest[..end].trim()).filter(|value| !value.is_empty())
+    }
+
+    /// Value of a keyword argument given as a string literal
+    pub fn string(&self, keyword: &str) -> Option<&str> {
+        let value = self.value(keyword)?;
+        ['"', '\'']
+            .into_iter()
+            .find_map(|q| value.strip_prefix(q)?.strip_suffix(q))
+    }
+}
+
+fn plugin_meta_args(source: &str) -> Option<String> {
+    let meta = &source[source.find("__plugin_meta__")?..];
+    let start = meta.find("PluginMetadata(")? + "PluginMetadata(".len();
+    let mut depth = 1;
+    for (i, c) in meta[start..].char_indices() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(meta[start..start + i].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(dir: &Path, dependencies: &str, init: &str) -> PluginProject {
+        fs::write(
+            dir.join("pyproject.toml"),
+            format!(
+                "[project]\nname = \"nonebot-plugin-weather\"\nversion = \"0.1.0\"\n\
+                description = \"Weather forecast\"\nrequires-python = \">=3.10\"\ndependencies = [{dependencies}]\n"
+            ),
+        )
+        .unwrap();
+        let module_dir = dir.join("src/nonebot_plugin_weather");
+        fs::create_dir_all(&module_dir).unwrap();
+        fs::write(module_dir.join("__init__.py"), init).unwrap();
+        PluginProject::load(dir).unwrap()
+    }
+
+    fn statuses(items: &[CheckItem]) -> Vec<(&str, CheckStatus)> {
+        items
+            .iter()
+            .map(|item| (item.name.as_str(), item.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_check_static() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let init = r#"
+from nonebot import require
+
+require("nonebot_plugin_localstore")
+require("nonebot_plugin_alconna.uniseg")
+
+__plugin_meta__ = PluginMetadata(
+    name="天气",
+    description="",
+    usage="",
+    type="application",
+    homepage="https://github.com/owner/nonebot-plugin-weather",
+    supported_adapters=inherit_supported_adapters("nonebot_plugin_alconna"),
+)
+"#;
+        let plugin = project(
+            temp_dir.path(),
+            r#""nonebot2>=2.4.0", "nonebot-plugin-localstore>=0.7", "nonebot-plugin-alconna[all]""#,
+            init,
+        );
+        assert_eq!(
+            statuses(&plugin.check_static())[4..],
+            [
+                ("project_link", CheckStatus::Pass),
+                ("module_name", CheckStatus::Pass),
+                ("__plugin_meta__", CheckStatus::Pass),
+                ("type", CheckStatus::Pass),
+                ("homepage", CheckStatus::Pass),
+                ("supported_adapters", CheckStatus::Pass),
+                ("dependencies", CheckStatus::Pass),
+            ]
+        );
+
+        let init = r#"
+require("nonebot_plugin_localstore")
+__plugin_meta__ = PluginMetadata(name="天气", type="plugin", homepage=None)
+"#;
+        let plugin = project(temp_dir.path(), r#""nonebot2>=2.4.0""#, init);
+        let items = plugin.check_static();
+        assert_eq!(
+            statuses(&items)[7..],
+            [
+                ("type", CheckStatus::Fail),
+                ("homepage", CheckStatus::Fail),
+                ("supported_adapters", CheckStatus::Fail),
+                ("dependencies", CheckStatus::Fail),
+            ]
+        );
+        assert!(items[10].message.ends_with("nonebot_plugin_localstore"));
+    }
+
+    #[test]
+    fn test_check_lenient_pyproject() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            r#"[project]
+name = "nonebot-plugin-weather"
+dynamic = ["version"]
+authors = [{ name = "owner" }]
+dependencies = ["nonebot2>=2.4.0"]
+
+[tool.pdm.version]
+source = "scm"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            temp_dir.path().join("nonebot_plugin_weather.py"),
+            r#"
+require("nonebot.plugins.echo")
+__plugin_meta__ = PluginMetadata(name="天气", type="application", homepage=None, supported_adapters=None)
+"#,
+        )
+        .unwrap();
+        let plugin = PluginProject::load(temp_dir.path()).unwrap();
+        let items = plugin.check_static();
+        assert_eq!(
+            statuses(&items)[..4],
+            [
+                ("project.name", CheckStatus::Pass),
+                ("project.version", CheckStatus::Pass),
+                ("project.description", CheckStatus::Fail),
+                ("project.requires-python", CheckStatus::Fail),
+            ]
+        );
+        assert_eq!(
+            items.last().map(|item| (item.name.as_str(), item.status)),
+            Some(("dependencies", CheckStatus::Pass))
+        );
+
+        fs::write(temp_dir.path().join("pyproject.toml"), "[tool.pdm]\n").unwrap();
+        let plugin = PluginProject::load(temp_dir.path()).unwrap();
+        assert_eq!(
+            statuses(&plugin.check_static())[..2],
+            [
+                ("project.name", CheckStatus::Fail),
+                ("project.version", CheckStatus::Fail),
+            ]
+        );
+    }
+}
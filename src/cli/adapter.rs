@@ -1,12 +1,14 @@
-use crate::config::get_cache_dir;
+use crate::config::NbrConfig;
+use crate::dist;
 use crate::log::StyledText;
+use crate::prompt::{Confirm, MultiSelect};
 use crate::pyproject::{Adapter, NbTomlEditor, PyProjectConfig};
-use crate::utils::terminal_utils;
+use crate::registry::{self, RegistryIndex};
+use crate::search::{self, Searchable};
+use crate::transaction::ProjectTransaction;
 use crate::uv;
 use anyhow::{Context, Result};
 use clap::Subcommand;
-use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Confirm, MultiSelect};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -43,6 +45,22 @@ pub struct RegistryAdapter {
     pub version: String,
 }
 
+impl Searchable for RegistryAdapter {
+    fn search_id(&self) -> &str {
+        &self.name
+    }
+
+    fn search_fields(&self) -> Vec<(&str, u32)> {
+        vec![
+            (self.name.as_str(), 3),
+            (self.project_link.as_str(), 3),
+            (self.module_name.as_str(), 2),
+            (self.author.as_str(), 2),
+            (self.desc.as_str(), 1),
+        ]
+    }
+}
+
 // registry adapter into adapter
 impl From<&RegistryAdapter> for Adapter {
     fn from(adapter: &RegistryAdapter) -> Self {
@@ -57,6 +75,8 @@ impl From<&RegistryAdapter> for Adapter {
 pub struct AdapterManager {
     /// HTTP client for registry requests
     client: Client,
+    /// Global nbr configuration
+    config: NbrConfig,
     /// Working directory
     work_dir: PathBuf,
     /// Registry adapters
@@ -83,17 +103,13 @@ impl AdapterManager {
 
         Ok(Self {
             client,
+            config: NbrConfig::load_with_project_or_default(&work_dir),
             work_dir,
             registry_adapters: OnceLock::new(),
             installed_adapters: OnceLock::new(),
         })
     }
 
-    fn get_cache_file(&self) -> Result<PathBuf> {
-        let cache_dir = get_cache_dir()?;
-        Ok(cache_dir.join("adapters.json"))
-    }
-
     fn set_registry_adapters(&self, adapters: HashMap<String, RegistryAdapter>) -> Result<()> {
         self.registry_adapters
             .set(adapters)
@@ -106,7 +122,7 @@ impl AdapterManager {
             .context("Registry adapters not initialized")
     }
 
-    /// Fetch registry adapters from registry.nonebot.dev
+    /// Fetch registry adapters from the configured registry sources
     pub async fn fetch_registry_adapters(
         &self,
         fetch_remote: bool,
@@ -115,36 +131,15 @@ impl AdapterManager {
             return Ok(adapters);
         }
 
-        // 从缓存中获取
-        let cache_file = self.get_cache_file()?;
-        if !fetch_remote && cache_file.exists() {
-            debug!("Loading adapters from cache: {}", cache_file.display());
-            let registry_adapters: HashMap<String, RegistryAdapter> =
-                serde_json::from_slice(&std::fs::read(&cache_file)?)?;
-            self.set_registry_adapters(registry_adapters)?;
-            return self.get_registry_adapters();
-        }
-
-        // 从 registry 获取
-        let spinner = terminal_utils::create_spinner("Fetching adapters from registry...");
-        let adapters_json_url = "https://registry.nonebot.dev/adapters.json";
-        let response = self.client.get(adapters_json_url).send().await?;
-
-        let adapters: Vec<RegistryAdapter> = response
-            .json()
-            .await
-            .context("Failed to parse adapter info")?;
-
-        // 解析成功后，结束 spinner
-        spinner.finish_and_clear();
-
-        let registry_adapters = adapters
-            .iter()
-            .map(|a| (a.name.to_owned(), a.clone()))
-            .collect::<HashMap<String, RegistryAdapter>>();
+        let registry_adapters = registry::fetch_index(
+            &self.client,
+            &self.config.registry,
+            RegistryIndex::Adapters,
+            fetch_remote,
+            |a: &RegistryAdapter| a.name.clone(),
+        )
+        .await?;
 
-        // 缓存到文件
-        std::fs::write(cache_file, serde_json::to_string(&registry_adapters)?)?;
         self.set_registry_adapters(registry_adapters)?;
         self.get_registry_adapters()
     }
@@ -194,10 +189,9 @@ impl AdapterManager {
         adapter_names.sort();
 
         let selected_adapters = if !adapter_names.is_empty() {
-            let selections = MultiSelect::with_theme(&ColorfulTheme::default())
-                .with_prompt("Which adapter(s) would you like to use")
-                .items(&adapter_names)
-                .interact()?;
+            let selections =
+                MultiSelect::new("Which adapter(s) would you like to use", &adapter_names)
+                    .interact()?;
 
             selections
                 .into_iter()
@@ -231,11 +225,7 @@ impl AdapterManager {
             .cyan_bold(format!("[{}]", selected_adapters_names).as_str())
             .to_string();
 
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(&prompt)
-            .default(true)
-            .interact()?
-        {
+        if !Confirm::new(prompt).default(true).interact()? {
             error!("{}", "Installation operation cancelled.");
             return Ok(());
         }
@@ -299,10 +289,11 @@ impl AdapterManager {
 
         // select adapters to uninstall
         let selected_adapters: Vec<&str> = {
-            let selections = MultiSelect::with_theme(&ColorfulTheme::default())
-                .with_prompt("Select installed adapter(s) to uninstall")
-                .items(&installed_adapters)
-                .interact()?;
+            let selections = MultiSelect::new(
+                "Select installed adapter(s) to uninstall",
+                &installed_adapters,
+            )
+            .interact()?;
 
             selections
                 .into_iter()
@@ -310,10 +301,6 @@ impl AdapterManager {
                 .collect()
         };
 
-        // Remove from configuration
-        NbTomlEditor::with_work_dir(Some(&self.work_dir))?
-            .remove_adapters(selected_adapters.to_vec())?;
-
         // Uninstall the package
         let registry_adapters = self.fetch_registry_adapters(false).await?;
 
@@ -343,6 +330,10 @@ impl AdapterManager {
                 .run()?;
         }
 
+        // Remove from configuration, after uv so a failed removal leaves it untouched
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?
+            .remove_adapters(selected_adapters.to_vec())?;
+
         StyledText::new(" ")
             .green_bold("✓ Successfully uninstalled adapters:")
             .cyan_bold(selected_adapters.join(", "))
@@ -351,30 +342,87 @@ impl AdapterManager {
         Ok(())
     }
 
-    /// List available and installed adapters
-    pub async fn list_adapters(&self, show_all: bool) -> Result<()> {
+    /// List available and installed adapters, filtered by query if given
+    pub async fn list_adapters(&self, show_all: bool, query: Option<&str>) -> Result<()> {
         let installed_adapters = self.get_installed_adapters_names();
         let adapters_map = self.fetch_registry_adapters(show_all).await?;
 
-        if show_all {
-            info!("All Adapters:");
-            adapters_map.iter().for_each(|(_, adapter)| {
-                self.display_adapter(adapter);
-            });
+        let mut adapters: Vec<&RegistryAdapter> = if show_all {
+            adapters_map.values().collect()
         } else {
             if installed_adapters.is_empty() {
                 warn!("No adapters installed.");
                 return Ok(());
             }
+            installed_adapters
+                .iter()
+                .filter_map(|name| adapters_map.get(*name))
+                .collect()
+        };
+        if let Some(query) = query {
+            adapters = search::rank(adapters, query)
+                .into_iter()
+                .map(|(_, a)| a)
+                .collect();
+            if adapters.is_empty() {
+                warn!("No adapters found for '{}'.", query);
+                return Ok(());
+            }
+        } else if show_all {
+            adapters.sort_by(|a, b| a.name.cmp(&b.name));
+        }
 
+        if show_all {
+            info!("All Adapters:");
+        } else {
             info!("Installed Adapters:");
-            installed_adapters.iter().for_each(|name| {
-                if let Some(adapter) = adapters_map.get(*name) {
-                    self.display_adapter(adapter);
-                }
-            });
         }
+        adapters
+            .iter()
+            .for_each(|adapter| self.display_adapter(adapter));
+
+        Ok(())
+    }
+
+    /// Installed adapter packages with a newer version, checked like `plugin list --outdated`
+    pub async fn outdated_adapters(&self, pypi: bool) -> Result<Vec<uv::Package>> {
+        let registry_adapters = self.fetch_registry_adapters(false).await?;
+        let adapter_packages: HashSet<String> = self
+            .get_installed_adapters_names()
+            .iter()
+            .filter_map(|name| registry_adapters.get(*name))
+            .map(|a| dist::normalize_name(&a.project_link))
+            .collect();
+        let is_adapter =
+            |p: &uv::Package| adapter_packages.contains(&dist::normalize_name(&p.name));
+
+        if pypi {
+            match uv::list(true).await {
+                Ok(packages) => return Ok(packages.into_iter().filter(is_adapter).collect()),
+                Err(e) => warn!("Failed to check PyPI, using the registry instead: {:#}", e),
+            }
+        }
+        let installed: Vec<uv::Package> = uv::list(false)
+            .await?
+            .into_iter()
+            .filter(is_adapter)
+            .collect();
+        let latest_versions = registry_adapters
+            .values()
+            .map(|a| (dist::normalize_name(&a.project_link), a.version.clone()))
+            .collect();
+        Ok(registry::outdated_packages(installed, &latest_versions))
+    }
 
+    /// List installed adapters with a newer version
+    pub async fn list_outdated_adapters(&self, pypi: bool) -> Result<()> {
+        let outdated = self.outdated_adapters(pypi).await?;
+        if outdated.is_empty() {
+            info!("All adapters are up to date.");
+            return Ok(());
+        }
+        info!("Outdated Adapters:");
+        outdated.iter().for_each(|adapter| adapter.display_info());
         Ok(())
     }
 
@@ -431,13 +479,34 @@ pub enum AdapterCommands {
     Uninstall,
     #[clap(about = "List installed adapters, show all adapters if --all is set")]
     List {
+        #[clap(help = "Search keyword, supports pinyin")]
+        query: Option<String>,
         #[clap(short, long, help = "Show all adapters")]
         all: bool,
+        #[clap(short, long, help = "Show outdated adapters")]
+        outdated: bool,
+        #[clap(
+            long,
+            help = "Check outdated adapters against PyPI instead of the cached registry"
+        )]
+        pypi: bool,
     },
 }
 
 /// Handle the adapter command
 pub async fn handle(commands: &AdapterCommands) -> Result<()> {
+    // 安装和卸载失败时回滚 pyproject.toml 和 uv.lock
+    let operation = match commands {
+        AdapterCommands::Install { .. } => "adapter install",
+        AdapterCommands::Uninstall => "adapter uninstall",
+        AdapterCommands::List { .. } => return handle_command(commands).await,
+    };
+    let transaction = ProjectTransaction::begin(Path::new("."), operation)?;
+    let result = handle_command(commands).await;
+    transaction.finish(result)
+}
+
+async fn handle_command(commands: &AdapterCommands) -> Result<()> {
     let adapter_manager = AdapterManager::new(None)?;
 
     match commands {
@@ -445,7 +514,20 @@ pub async fn handle(commands: &AdapterCommands) -> Result<()> {
             adapter_manager.install_adapters(*fetch_remote).await?
         }
         AdapterCommands::Uninstall => adapter_manager.uninstall_adapters().await?,
-        AdapterCommands::List { all } => adapter_manager.list_adapters(*all).await?,
+        AdapterCommands::List {
+            query,
+            all,
+            outdated,
+            pypi,
+        } => {
+            if *outdated {
+                adapter_manager.list_outdated_adapters(*pypi).await?
+            } else {
+                adapter_manager
+                    .list_adapters(*all, query.as_deref())
+                    .await?
+            }
+        }
     }
     Ok(())
 }
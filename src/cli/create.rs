@@ -2,6 +2,7 @@ use super::adapter::{AdapterManager, RegistryAdapter};
 use super::common;
 use super::docker;
 use crate::error::Error;
+use crate::prompt::{Confirm, Input, MultiSelect, Select};
 use crate::pyproject::{
     BuildSystem, DependencyGroupItem, DependencyGroups, NbTomlEditor, Nonebot, Project,
     PyProjectConfig, Tool,
@@ -9,8 +10,6 @@ use crate::pyproject::{
 use crate::uv;
 use anyhow::{Context, Result};
 use clap::{Args, ValueEnum};
-use dialoguer::theme::ColorfulTheme;
-use dialoguer::{Confirm, Input, MultiSelect, Select};
 use std::collections::HashSet;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
@@ -131,13 +130,12 @@ pub async fn handle(args: CreateArgs) -> Result<()> {
 
 fn check_directory_exists(output_dir: &Path) -> Result<()> {
     if output_dir.exists() {
-        let should_continue = Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!(
-                "Directory '{}' already exists. Continue?",
-                output_dir.display()
-            ))
-            .default(false)
-            .interact()?;
+        let should_continue = Confirm::new(format!(
+            "Directory '{}' already exists. Continue?",
+            output_dir.display()
+        ))
+        .default(false)
+        .interact()?;
 
         if !should_continue {
             return Err(Error::Cancelled.into());
@@ -148,17 +146,16 @@ fn check_directory_exists(output_dir: &Path) -> Result<()> {
 
 /// Confirm whether to generate Dockerfile and Docker Compose configuration
 fn confirm_gen_docker() -> Result<bool> {
-    let gen_dockerfile = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt("Would you like to generate Dockerfile and Docker Compose configuration?")
-        .default(true)
-        .interact()?;
+    let gen_dockerfile =
+        Confirm::new("Would you like to generate Dockerfile and Docker Compose configuration?")
+            .default(true)
+            .interact()?;
     Ok(gen_dockerfile)
 }
 
 /// Confirm whether to create a virtual environment now
 fn confirm_create_venv() -> Result<bool> {
-    let create_venv = Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt("Would you like to create a virtual environment now?")
+    let create_venv = Confirm::new("Would you like to create a virtual environment now?")
         .default(true)
         .interact()?;
     Ok(create_venv)
@@ -257,10 +254,9 @@ async fn gather_project_options(
 }
 
 fn input_project_name() -> anyhow::Result<String> {
-    Input::<String>::with_theme(&ColorfulTheme::default())
-        .with_prompt("Project name")
-        .default("awesome-bot".to_string())
-        .validate_with(|input: &String| -> Result<()> {
+    Input::new("Project name")
+        .default("awesome-bot")
+        .validate_with(|input| {
             if input.contains(" ") {
                 anyhow::bail!("Project name cannot contain spaces")
             } else {
@@ -274,9 +270,7 @@ fn input_project_name() -> anyhow::Result<String> {
 fn select_environment() -> Result<Environment> {
     let envs = Environment::value_variants();
 
-    let selected_idx = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Which environment are you in now")
-        .items(envs)
+    let selected_idx = Select::new("Which environment are you in now", envs)
         .default(0)
         .interact()?;
     Ok(envs[selected_idx].clone())
@@ -284,9 +278,7 @@ fn select_environment() -> Result<Environment> {
 
 fn select_drivers() -> Result<Vec<String>> {
     let drivers = Driver::value_variants();
-    let selected_drivers = MultiSelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Which driver(s) would you like to use")
-        .items(drivers)
+    let selected_drivers = MultiSelect::new("Which driver(s) would you like to use", drivers)
         // 默认选择前三个
         .defaults(&[true; 3])
         .interact()?;
@@ -308,10 +300,8 @@ fn select_template() -> Result<Template> {
         "bootstrap - Basic NoneBot project template",
         "simple - Simple bot template with basic plugins",
     ];
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a template")
+    let selection = Select::new("Select a template", &template_prompts)
         .default(0)
-        .items(&template_prompts)
         .interact()?;
 
     match selection {
@@ -323,9 +313,7 @@ fn select_template() -> Result<Template> {
 
 fn select_dev_tools() -> Result<Vec<DevTool>> {
     let dev_tools = DevTool::value_variants();
-    let selected_dev_tools = MultiSelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Which dev tool(s) would you like to use")
-        .items(dev_tools)
+    let selected_dev_tools = MultiSelect::new("Which dev tool(s) would you like to use", dev_tools)
         .defaults(&[true; 3])
         .interact()?;
     let selected_dev_tools = selected_dev_tools
@@ -338,14 +326,15 @@ fn select_dev_tools() -> Result<Vec<DevTool>> {
 // 选择内置插件
 fn select_builtin_plugins() -> Result<Vec<String>> {
     let builtin_plugins = BuiltinPlugin::value_variants();
-    let selected_plugins = MultiSelect::with_theme(&ColorfulTheme::default())
-        .with_prompt("Which builtin plugin(s) would you like to use")
-        .items(builtin_plugins)
-        .defaults(&vec![true; builtin_plugins.len().min(1)])
-        .interact()?
-        .into_iter()
-        .map(|i| builtin_plugins[i].to_string())
-        .collect();
+    let selected_plugins = MultiSelect::new(
+        "Which builtin plugin(s) would you like to use",
+        builtin_plugins,
+    )
+    .defaults(&vec![true; builtin_plugins.len().min(1)])
+    .interact()?
+    .into_iter()
+    .map(|i| builtin_plugins[i].to_string())
+    .collect();
     Ok(selected_plugins)
 }
 
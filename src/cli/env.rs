@@ -1,5 +1,7 @@
 use crate::cli::EnvCommands;
+use crate::cli::plugin::PluginManager;
 use crate::log::StyledText;
+use crate::pyproject::PyProjectConfig;
 use crate::utils::{process_utils, terminal_utils};
 use crate::uv::{self, Package};
 use anyhow::{Context, Result};
@@ -129,6 +131,8 @@ pub struct ProjectInfo {
     pub root_path: PathBuf,
     pub bot_file: Option<PathBuf>,
     pub plugins_dir: Option<PathBuf>,
+    /// Directories in tool.nonebot.plugin_dirs that do not exist
+    pub missing_plugin_dirs: Vec<String>,
     pub is_git_repo: bool,
 }
 
@@ -243,6 +247,8 @@ pub enum Issue {
     GitRepoNotInitialized,
     /// Plugins directory is not configured
     PluginsDirNotConfigured,
+    /// A directory in tool.nonebot.plugin_dirs does not exist
+    PluginDirNotFound(String),
     /// Low system memory available (< 512 MB)
     LowSystemMemory,
     /// Low disk space available (< 512 MB)
@@ -260,6 +266,9 @@ impl fmt::Display for Issue {
             Self::GitNotInstalled => write!(f, "Git is not installed"),
             Self::GitRepoNotInitialized => write!(f, "Git repository is not initialized"),
             Self::PluginsDirNotConfigured => write!(f, "Plugins directory is not configured"),
+            Self::PluginDirNotFound(dir) => {
+                write!(f, "Plugin directory '{}' does not exist", dir)
+            }
             Self::LowSystemMemory => write!(f, "Low system memory available (< 512 MB)"),
             Self::LowDiskSpace => write!(f, "Low disk space available (< 512 MB)"),
         }
@@ -316,6 +325,12 @@ impl Issue {
                     .cyan("PLUGINS_DIR = \"plugins\"")
                     .println();
             }
+            Issue::PluginDirNotFound(dir) => {
+                StyledText::new("")
+                    .text("  • Create the directory or remove it from tool.nonebot.plugin_dirs: ")
+                    .cyan(format!("nbr plugin dirs remove {dir}"))
+                    .println();
+            }
             Issue::VirtualEnvNotActivated => {
                 StyledText::new("")
                     .text("  • Activate the virtual environment: ")
@@ -448,7 +463,9 @@ impl EnvironmentChecker {
         let location = package.location.unwrap_or("Unknown".to_string());
 
         let adapters = Self::get_installed_adapters(&python_info.site_packages);
-        let plugins = Self::get_installed_plugins(&python_info.site_packages);
+        let plugins = PluginManager::new(Some(self.work_dir.clone()))?
+            .filter_plugins(python_info.site_packages.clone())
+            .await;
 
         Ok(NoneBotInfo {
             version,
@@ -467,15 +484,6 @@ impl EnvironmentChecker {
             .collect()
     }
 
-    /// Get installed plugins
-    fn get_installed_plugins(packages: &[Package]) -> Vec<Package> {
-        packages
-            .iter()
-            .filter(|p| p.name.starts_with("nonebot") && p.name.contains("plugin"))
-            .cloned()
-            .collect()
-    }
-
     /// Get project information
     fn get_project_info(&self) -> Option<ProjectInfo> {
         let name = self
@@ -503,6 +511,14 @@ impl EnvironmentChecker {
             None
         };
 
+        let missing_plugin_dirs = PyProjectConfig::parse(Some(&self.work_dir))
+            .ok()
+            .and_then(|config| config.nonebot()?.plugin_dirs.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|dir| !self.work_dir.join(dir).is_dir())
+            .collect();
+
         // Check if it's a git repository
         let is_git_repo = self.work_dir.join(".git").exists();
 
@@ -511,6 +527,7 @@ impl EnvironmentChecker {
             root_path,
             bot_file,
             plugins_dir,
+            missing_plugin_dirs,
             is_git_repo,
         })
     }
@@ -650,6 +667,13 @@ impl EnvironmentChecker {
             issues.push(Issue::NoVirtualEnvironmentDetected);
         }
 
+        // Check tool.nonebot.plugin_dirs
+        if let Some(ref project_info) = env_info.project_info {
+            for dir in &project_info.missing_plugin_dirs {
+                issues.push(Issue::PluginDirNotFound(dir.clone()));
+            }
+        }
+
         // Check system resources
         let available_gb = env_info.system_info.available_memory as f64 / 1_073_741_824.0;
         if available_gb < 0.5 {
@@ -1,8 +1,7 @@
 use crate::log::StyledText;
+use crate::prompt::Confirm;
 use crate::pyproject::PyProjectConfig;
 use anyhow::{Context, Result};
-use dialoguer::Confirm;
-use dialoguer::theme::ColorfulTheme;
 use std::fmt::Write;
 use std::fs;
 use std::path::Path;
@@ -16,8 +15,7 @@ pub async fn generate_bot_file(work_dir: &Path, force: bool) -> Result<()> {
     // Check if file already exists
     if bot_path.exists()
         && !force
-        && !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!("File '{filename}' already exists. Overwrite"))
+        && !Confirm::new(format!("File '{filename}' already exists. Overwrite"))
             .default(false)
             .interact()?
     {
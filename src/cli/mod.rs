@@ -31,10 +31,25 @@ pub struct Cli {
     pub commands: NbrCommands,
     #[clap(short, long, action = ArgAction::Count, help = "Verbose level, -v: DEBUG, -vv: TRACE")]
     pub verbose: u8,
+    #[clap(
+        short,
+        long,
+        global = true,
+        help = "Answer yes to every confirmation and accept other defaults without prompting"
+    )]
+    pub yes: bool,
+    #[clap(
+        long,
+        global = true,
+        help = "Accept the default answer of every prompt without prompting, also enabled by NBR_NO_INPUT=1"
+    )]
+    pub no_input: bool,
 }
 
 impl Cli {
     pub async fn run(self) -> anyhow::Result<()> {
+        crate::prompt::set_no_input(self.no_input);
+        crate::prompt::set_assume_yes(self.yes);
         match self.commands {
             NbrCommands::Create(create_args) => create::handle(create_args).await?,
             NbrCommands::Run { file, reload } => run::handle(file, reload).await?,
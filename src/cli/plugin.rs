@@ -1,27 +1,40 @@
-use crate::config::get_cache_dir;
+use crate::changelog::UpdatePreview;
+use crate::check::{CheckItem, CheckStatus, PluginProject};
+use crate::cli::env::find_python_executable;
+use crate::config::{NbrConfig, ProjectConfig};
+use crate::dist::{self, Distribution};
+use crate::git::{self, GitSource};
+use crate::graph::DependencyGraph;
+use crate::lock::LockDiff;
 use crate::log::StyledText;
-use crate::pyproject::NbTomlEditor;
-use crate::utils::terminal_utils;
-use crate::uv::{self, CmdBuilder, Package};
+use crate::prompt::{Confirm, Input, MultiSelect, Select};
+use crate::publish::{self, RegistrySubmission, Tag};
+use crate::pyproject::{self, NbTomlEditor, PyProjectConfig};
+use crate::registry::{self, RegistryIndex};
+use crate::search::{self, Searchable};
+use crate::transaction::ProjectTransaction;
+use crate::utils::{process_utils, terminal_utils};
+use crate::uv::{self, Package};
 use anyhow::{Context, Result};
-use clap::Subcommand;
-use dialoguer::Confirm;
-use dialoguer::theme::ColorfulTheme;
+use clap::{Subcommand, ValueEnum};
+use futures_util::future::join_all;
 use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+use strum::Display;
 use tracing::{debug, error, info, warn};
 
 #[derive(Subcommand)]
 pub enum PluginCommands {
     #[clap(about = "Install a plugin")]
     Install {
-        #[clap(help = "Plugin name")]
-        name: String,
+        #[clap(required = true, help = "Plugin name(s), git url(s) or local path(s)")]
+        names: Vec<String>,
         #[clap(short, long, help = "Specify the index url")]
         index: Option<String>,
         #[clap(short, long, help = "Upgrade the plugin")]
@@ -30,6 +43,13 @@ pub enum PluginCommands {
         reinstall: bool,
         #[clap(short, long, help = "Fetch plugins from remote")]
         fetch_remote: bool,
+        #[clap(
+            long,
+            help = "Install even if the plugin does not support the configured adapters"
+        )]
+        allow_incompatible: bool,
+        #[clap(short, long, help = "Install local path plugins in editable mode")]
+        editable: bool,
     },
     #[clap(about = "Uninstall a plugin")]
     Uninstall {
@@ -40,10 +60,69 @@ pub enum PluginCommands {
     List {
         #[clap(short, long, help = "Show outdated plugins")]
         outdated: bool,
+        #[clap(
+            long,
+            help = "Check outdated plugins against PyPI instead of the cached registry"
+        )]
+        pypi: bool,
+        #[clap(
+            long,
+            help = "Install missing plugins and register unloaded plugins in tool.nonebot.plugins"
+        )]
+        fix: bool,
+        #[clap(
+            short,
+            long,
+            conflicts_with_all = ["outdated", "pypi", "fix"],
+            help = "List local plugins in tool.nonebot.plugin_dirs"
+        )]
+        local: bool,
+    },
+    #[clap(about = "Manage tool.nonebot.plugin_dirs")]
+    Dirs {
+        #[clap(subcommand)]
+        commands: PluginDirsCommands,
+    },
+    #[clap(about = "Show the config fields of a plugin, optionally append them to the .env file")]
+    Config {
+        #[clap(help = "Plugin package name or module name")]
+        name: String,
+        #[clap(
+            short,
+            long,
+            help = "Append missing fields as comments to the active .env.{ENVIRONMENT} file"
+        )]
+        write: bool,
+    },
+    #[clap(about = "Disable a plugin without uninstalling it")]
+    Disable {
+        #[clap(help = "Plugin package name or module name")]
+        name: String,
+    },
+    #[clap(about = "Enable a disabled plugin")]
+    Enable {
+        #[clap(help = "Plugin package name or module name")]
+        name: String,
+    },
+    #[clap(about = "Show plugin details from registry, environment and pyproject.toml")]
+    Info {
+        #[clap(help = "Plugin package name or module name")]
+        name: String,
+        #[clap(short, long, help = "Fetch plugins from remote")]
+        fetch_remote: bool,
+    },
+    #[clap(about = "Show dependencies between installed plugins and adapters")]
+    Tree {
+        #[clap(help = "Only show this plugin, package name or module name")]
+        name: Option<String>,
+        #[clap(short, long, help = "Show which plugins require each plugin")]
+        invert: bool,
+        #[clap(long, value_enum, default_value_t = TreeFormat::Text, help = "Output format")]
+        format: TreeFormat,
     },
     #[clap(about = "Search plugins in registry")]
     Search {
-        #[clap(help = "Search keyword")]
+        #[clap(help = "Search keyword, supports pinyin and pinyin initials")]
         query: String,
         #[clap(
             short,
@@ -54,6 +133,23 @@ pub enum PluginCommands {
         limit: usize,
         #[clap(short, long, help = "Fetch plugins from remote")]
         fetch_remote: bool,
+        #[clap(short, long, help = "Only show plugins with this tag")]
+        tag: Option<String>,
+        #[clap(short, long, help = "Only show official plugins")]
+        official: bool,
+        #[clap(
+            short,
+            long,
+            help = "Only show plugins supporting this adapter, e.g. onebot.v11"
+        )]
+        adapter: Option<String>,
+        #[clap(
+            long = "type",
+            help = "Only show plugins of this type, e.g. application"
+        )]
+        plugin_type: Option<String>,
+        #[clap(short, long, value_enum, default_value_t = SearchSort::Relevance, help = "Sort results")]
+        sort: SearchSort,
     },
     #[clap(about = "Update plugin(s)")]
     Update {
@@ -61,43 +157,259 @@ pub enum PluginCommands {
         name: Option<String>,
         #[clap(short, long, help = "Update all plugins")]
         all: bool,
+        #[clap(
+            long,
+            help = "Check outdated plugins against PyPI instead of the cached registry"
+        )]
+        pypi: bool,
         #[clap(short, long, help = "Reinstall the plugin")]
         reinstall: bool,
+        #[clap(long, help = "Show the uv.lock changes without updating")]
+        dry_run: bool,
     },
     #[clap(about = "Reset nonebot plugins, remove invalid plugins and add missing plugins")]
     Reset,
     #[clap(about = "Create a new plugin")]
-    Create,
+    Create {
+        #[clap(help = "Plugin name shown in PluginMetadata, e.g. 天气")]
+        name: Option<String>,
+        #[clap(
+            short,
+            long,
+            help = "Importable module name, derived from the plugin name by default"
+        )]
+        module_name: Option<String>,
+        #[clap(short, long, value_enum, help = "Plugin layout")]
+        layout: Option<PluginLayout>,
+        #[clap(
+            short,
+            long,
+            help = "Plugin directory, one of tool.nonebot.plugin_dirs"
+        )]
+        dir: Option<String>,
+        #[clap(short, long, help = "Generate a nonebug test")]
+        test: bool,
+        #[clap(long, conflicts_with = "test", help = "Do not generate a nonebug test")]
+        no_test: bool,
+    },
+    #[clap(about = "Check a plugin project against the registry publishing rules")]
+    Check {
+        #[clap(help = "Plugin project directory, defaults to the current directory")]
+        path: Option<PathBuf>,
+        #[clap(
+            long,
+            help = "Skip installing and loading the plugin in an isolated venv"
+        )]
+        skip_import: bool,
+    },
+    #[clap(about = "Build a plugin and prepare its registry submission")]
+    Publish {
+        #[clap(help = "Plugin project directory, defaults to the current directory")]
+        path: Option<PathBuf>,
+        #[clap(short, long, help = "Upload the distributions with uv publish")]
+        upload: bool,
+        #[clap(
+            long,
+            help = "Upload url, defaults to tool.nbr.publish.publish-url or PyPI"
+        )]
+        publish_url: Option<String>,
+        #[clap(
+            short,
+            long,
+            help = "Registry tag, `label` or `label:#rrggbb`, up to 3"
+        )]
+        tag: Vec<String>,
+        #[clap(long, help = "Skip plugin check")]
+        skip_check: bool,
+        #[clap(
+            long,
+            help = "Skip installing and loading the plugin in an isolated venv"
+        )]
+        skip_import: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PluginDirsCommands {
+    #[clap(about = "Add directories to tool.nonebot.plugin_dirs")]
+    Add {
+        #[clap(required = true, help = "Directories relative to the project root")]
+        dirs: Vec<String>,
+    },
+    #[clap(about = "Remove directories from tool.nonebot.plugin_dirs")]
+    Remove {
+        #[clap(required = true, help = "Directories relative to the project root")]
+        dirs: Vec<String>,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq, Display)]
+#[clap(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PluginLayout {
+    /// A single `<plugin>.py` module
+    File,
+    /// A `<plugin>/` package with `__init__.py` and `config.py`
+    Package,
+}
+
+impl PluginCommands {
+    /// Name of the command if it changes `pyproject.toml` or `uv.lock`
+    fn mutation(&self) -> Option<&'static str> {
+        match self {
+            Self::Install { .. } => Some("plugin install"),
+            Self::Uninstall { .. } => Some("plugin uninstall"),
+            Self::List { fix: true, .. } => Some("plugin list --fix"),
+            Self::Dirs { .. } => Some("plugin dirs"),
+            Self::Disable { .. } => Some("plugin disable"),
+            Self::Enable { .. } => Some("plugin enable"),
+            Self::Update { dry_run: false, .. } => Some("plugin update"),
+            Self::Reset => Some("plugin reset"),
+            _ => None,
+        }
+    }
 }
 
 pub async fn handle(commands: &PluginCommands) -> Result<()> {
+    // 修改项目的命令失败时回滚 pyproject.toml 和 uv.lock
+    let Some(operation) = commands.mutation() else {
+        return handle_command(commands).await;
+    };
+    let transaction = ProjectTransaction::begin(Path::new("."), operation)?;
+    let result = handle_command(commands).await;
+    transaction.finish(result)
+}
+
+async fn handle_command(commands: &PluginCommands) -> Result<()> {
     let mut manager = PluginManager::new(None)?;
     match commands {
         PluginCommands::Install {
-            name,
+            names,
             index,
             upgrade,
             reinstall,
             fetch_remote,
+            allow_incompatible,
+            editable,
         } => {
-            let options = InstallOptions::new(name, *upgrade, *reinstall, index.as_deref())?;
+            let options = names
+                .iter()
+                .map(|name| {
+                    let mut options =
+                        InstallOptions::new(name, *upgrade, *reinstall, index.as_deref())?;
+                    options.allow_incompatible = *allow_incompatible;
+                    options.editable = *editable;
+                    Ok(options)
+                })
+                .collect::<Result<Vec<_>>>()?;
             manager.install(options, *fetch_remote).await?
         }
         PluginCommands::Uninstall { name } => manager.uninstall(name).await?,
-        PluginCommands::List { outdated } => manager.list(*outdated).await?,
+        PluginCommands::List {
+            outdated,
+            pypi,
+            fix,
+            local,
+        } => {
+            if *local {
+                manager.list_local()?
+            } else {
+                manager.list(*outdated, *pypi, *fix).await?
+            }
+        }
+        PluginCommands::Dirs { commands } => match commands {
+            PluginDirsCommands::Add { dirs } => manager.add_plugin_dirs(dirs)?,
+            PluginDirsCommands::Remove { dirs } => manager.remove_plugin_dirs(dirs)?,
+        },
+        PluginCommands::Config { name, write } => manager.config(name, *write).await?,
+        PluginCommands::Disable { name } => manager.disable(name)?,
+        PluginCommands::Enable { name } => manager.enable(name)?,
+        PluginCommands::Info { name, fetch_remote } => manager.info(name, *fetch_remote).await?,
+        PluginCommands::Tree {
+            name,
+            invert,
+            format,
+        } => manager.tree(name.as_deref(), *invert, format).await?,
         PluginCommands::Search {
             query,
             limit,
             fetch_remote,
-        } => manager.search_plugins(query, *limit, *fetch_remote).await?,
+            tag,
+            official,
+            adapter,
+            plugin_type,
+            sort,
+        } => {
+            let filter = SearchFilter {
+                tag: tag.clone(),
+                official: *official,
+                adapter: adapter.clone(),
+                plugin_type: plugin_type.clone(),
+                sort: sort.clone(),
+            };
+            manager
+                .search_plugins(query, *limit, &filter, *fetch_remote)
+                .await?
+        }
         PluginCommands::Update {
             name,
             all,
+            pypi,
             reinstall,
-        } => manager.update(name.as_deref(), *all, *reinstall).await?,
+            dry_run,
+        } => {
+            manager
+                .update(name.as_deref(), *all, *pypi, *reinstall, *dry_run)
+                .await?
+        }
         PluginCommands::Reset => manager.reset().await?,
-        PluginCommands::Create => {
-            unimplemented!()
+        PluginCommands::Create {
+            name,
+            module_name,
+            layout,
+            dir,
+            test,
+            no_test,
+        } => {
+            let work_dir = std::env::current_dir()?;
+            // 两个选项都未指定时询问
+            let with_test = match (test, no_test) {
+                (true, _) => Some(true),
+                (_, true) => Some(false),
+                _ => None,
+            };
+            let options = gather_plugin_options(
+                &work_dir,
+                name.clone(),
+                module_name.clone(),
+                layout.clone(),
+                dir.clone(),
+                with_test,
+            )?;
+            create_plugin(&work_dir, &options)?;
+        }
+        PluginCommands::Check { path, skip_import } => {
+            let dir = path.clone().unwrap_or_else(|| PathBuf::from("."));
+            check_plugin(&dir, *skip_import).await?;
+        }
+        PluginCommands::Publish {
+            path,
+            upload,
+            publish_url,
+            tag,
+            skip_check,
+            skip_import,
+        } => {
+            let dir = path.clone().unwrap_or_else(|| PathBuf::from("."));
+            let options = PublishOptions {
+                upload: *upload,
+                publish_url: publish_url.as_deref(),
+                tags: tag.iter().map(|t| Tag::parse(t)).collect::<Result<_>>()?,
+                skip_check: *skip_check,
+                skip_import: *skip_import,
+                ..Default::default()
+            };
+            publish_plugin(&dir, options).await?;
         }
     }
     Ok(())
@@ -144,10 +456,127 @@ pub struct RegistryPlugin {
     pub skip_test: bool,
 }
 
+impl Searchable for RegistryPlugin {
+    fn search_id(&self) -> &str {
+        &self.project_link
+    }
+
+    fn search_fields(&self) -> Vec<(&str, u32)> {
+        let mut fields = vec![
+            (self.project_link.as_str(), 3),
+            (self.name.as_str(), 3),
+            (self.author.as_str(), 2),
+            (self.desc.as_str(), 1),
+        ];
+        fields.extend(self.tag_labels().map(|label| (label, 2)));
+        fields
+    }
+}
+
+impl RegistryPlugin {
+    /// Labels of the plugin tags
+    pub fn tag_labels(&self) -> impl Iterator<Item = &str> {
+        self.tags
+            .iter()
+            .filter_map(|t| t.get("label"))
+            .map(|s| s.as_str())
+    }
+
+    /// Whether the plugin supports at least one of the adapters
+    ///
+    /// Always compatible when no adapter is configured or the plugin supports all adapters.
+    pub fn is_compatible_with(&self, adapters: &[String]) -> bool {
+        adapters.is_empty() || adapters.iter().any(|a| self.supports_adapter(a))
+    }
+
+    /// Whether the plugin supports the adapter, `None` in registry means all adapters
+    pub fn supports_adapter(&self, adapter: &str) -> bool {
+        let adapter = normalize_adapter_module(adapter);
+        self.supported_adapters.as_ref().is_none_or(|adapters| {
+            adapters
+                .iter()
+                .any(|a| normalize_adapter_module(a) == adapter)
+        })
+    }
+}
+
+/// Normalize adapter module names, `~onebot.v11` / `onebot.v11` -> `nonebot.adapters.onebot.v11`
+pub fn normalize_adapter_module(adapter: &str) -> String {
+    let adapter = adapter.trim().to_lowercase();
+    if let Some(stripped) = adapter.strip_prefix('~') {
+        format!("nonebot.adapters.{stripped}")
+    } else if adapter.starts_with("nonebot.") {
+        adapter
+    } else {
+        format!("nonebot.adapters.{adapter}")
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq)]
+#[clap(rename_all = "lowercase")]
+pub enum TreeFormat {
+    /// Indented tree
+    Text,
+    /// Graphviz DOT
+    Dot,
+    Json,
+}
+
+#[derive(ValueEnum, Debug, Clone, Default, PartialEq)]
+#[clap(rename_all = "lowercase")]
+pub enum SearchSort {
+    /// Best matches first
+    #[default]
+    Relevance,
+    /// Most recently published first
+    Time,
+}
+
+/// Filters applied to registry plugin search
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    pub tag: Option<String>,
+    pub official: bool,
+    pub adapter: Option<String>,
+    pub plugin_type: Option<String>,
+    pub sort: SearchSort,
+}
+
+impl SearchFilter {
+    pub fn matches(&self, plugin: &RegistryPlugin) -> bool {
+        if self.official && !plugin.is_official {
+            return false;
+        }
+        if let Some(ref tag) = self.tag
+            && !plugin
+                .tag_labels()
+                .any(|label| label.eq_ignore_ascii_case(tag))
+        {
+            return false;
+        }
+        if let Some(ref plugin_type) = self.plugin_type
+            && !plugin
+                .plugin_type
+                .as_deref()
+                .is_some_and(|t| t.eq_ignore_ascii_case(plugin_type))
+        {
+            return false;
+        }
+        if let Some(ref adapter) = self.adapter
+            && !plugin.supports_adapter(adapter)
+        {
+            return false;
+        }
+        true
+    }
+}
+
 /// Plugin manager
 pub struct PluginManager {
     /// HTTP client for registry requests
     client: Client,
+    /// Global nbr configuration
+    config: NbrConfig,
     /// Working directory
     work_dir: PathBuf,
     /// Registry plugins, key is package name
@@ -160,16 +589,34 @@ impl Default for PluginManager {
     }
 }
 
+/// A plugin only required by an uninstalled plugin
+#[derive(Debug, Clone)]
+struct Orphan {
+    package: String,
+    /// Resolved while the package is still installed
+    module: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct InstallOptions<'a> {
-    pub name: &'a str,
+    /// Package name, for git sources the repository name until the package is fetched
+    pub name: String,
+    /// Guessed from the name, the real module is read from the installed metadata
     pub module_name: String,
-    pub git_url: Option<&'a str>,
+    pub git: Option<GitSource>,
+    /// Local package directory
+    pub path: Option<&'a str>,
+    /// Direct url of a wheel or source archive
+    pub url: Option<&'a str>,
+    /// Install the local package in editable mode
+    pub editable: bool,
     pub upgrade: bool,
     pub reinstall: bool,
     pub index_url: Option<&'a str>,
     pub extras: Option<Vec<&'a str>>,
     pub specifier: Option<&'a str>,
+    /// Skip the adapter compatibility check
+    pub allow_incompatible: bool,
 }
 
 impl<'a> InstallOptions<'a> {
@@ -179,19 +626,38 @@ impl<'a> InstallOptions<'a> {
         reinstall: bool,
         index_url: Option<&'a str>,
     ) -> Result<Self> {
-        let (name, git_url, extras, specifier) = if input.starts_with("git+") {
-            const GIT_URL_PATTERN: &str = r"nonebot-plugin-([^/.@]+)";
-            let re = Regex::new(GIT_URL_PATTERN).context("Invalid regex pattern")?;
-            let captures = re
-                .captures(input)
-                .with_context(|| format!("Invalid plugin name: {}", input))?;
-            let name = captures
-                .get(0)
-                .map(|m| m.as_str())
-                .context("Regex should have at least one capture group")?;
-            (name, Some(input), None, None)
+        let (name, git, url, extras, specifier) = if GitSource::is_git(input) {
+            let git = GitSource::parse(input)?;
+            (git.repo_name().to_string(), Some(git), None, None, None)
+        } else if is_direct_url(input) {
+            // 包名以安装后的元数据为准，这里只从文件名推断
+            (direct_url_name(input), None, Some(input), None, None)
+        } else if is_local_path(input) {
+            // 本地包的包名和模块名从源码目录读取
+            let package_dir = Path::new(input);
+            if !package_dir.is_dir() {
+                anyhow::bail!("Local plugin directory '{}' does not exist", input);
+            }
+            let name = pyproject::project_name(package_dir)?;
+            let module_name =
+                dist::source_module(package_dir, &name).unwrap_or_else(|| name.replace("-", "_"));
+            return Ok(Self {
+                name,
+                module_name,
+                git: None,
+                path: Some(input),
+                url: None,
+                editable: false,
+                upgrade,
+                reinstall,
+                index_url,
+                extras: None,
+                specifier: None,
+                allow_incompatible: false,
+            });
         } else {
-            const PATTERN: &str = r"^([a-zA-Z0-9_-]+)(?:\[([a-zA-Z0-9_,\s]*)\])?(?:\s*((?:==|>=|<=|>|<|~=)\s*[a-zA-Z0-9\.]+))?$";
+            // name[extra1,extra2]>=1.0,<2.0
+            const PATTERN: &str = r"^([a-zA-Z0-9_.-]+)\s*(?:\[([a-zA-Z0-9_,.\s-]*)\])?\s*((?:===|==|!=|~=|>=|<=|>|<)\s*[a-zA-Z0-9.*+!-]+(?:\s*,\s*(?:===|==|!=|~=|>=|<=|>|<)\s*[a-zA-Z0-9.*+!-]+)*)?$";
             let re = Regex::new(PATTERN).context("Invalid regex pattern")?;
             let captures = re
                 .captures(input)
@@ -200,11 +666,15 @@ impl<'a> InstallOptions<'a> {
                 .get(1)
                 .map(|m| m.as_str())
                 .context("Regex should have at least one capture group")?;
-            let extras = captures
-                .get(2)
-                .map(|m| m.as_str().split(',').collect::<Vec<&str>>());
+            let extras = captures.get(2).map(|m| {
+                m.as_str()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|extra| !extra.is_empty())
+                    .collect::<Vec<&str>>()
+            });
             let specifier = captures.get(3).map(|m| m.as_str());
-            (name, None, extras, specifier)
+            (name.to_string(), None, None, extras, specifier)
         };
 
         let module_name = name.replace("-", "_");
@@ -212,40 +682,53 @@ impl<'a> InstallOptions<'a> {
         Ok(Self {
             name,
             module_name,
-            git_url,
+            git,
+            path: None,
+            url,
+            editable: false,
             upgrade,
             reinstall,
             index_url,
             extras,
             specifier,
+            allow_incompatible: false,
         })
     }
 
-    pub fn install(&self) -> Result<()> {
-        let mut args = vec!["add"];
-
-        if let Some(git_url) = self.git_url {
-            args.push(git_url);
-        } else {
-            args.push(self.name);
-        }
-
-        if self.upgrade {
-            args.push("--upgrade");
+    /// Requirement passed to `uv add`, extras and specifier are kept on the package
+    /// so they end up in `project.dependencies` as written
+    pub fn requirement(&self) -> String {
+        if let Some(ref git) = self.git {
+            return git.requirement();
         }
-        if self.reinstall {
-            args.push("--reinstall");
+        if let Some(path) = self.path.or(self.url) {
+            return path.to_string();
         }
-        if let Some(index_url) = self.index_url {
-            args.push("--index-url");
-            args.push(index_url);
+        let mut requirement = self.name.clone();
+        if let Some(ref extras) = self.extras
+            && !extras.is_empty()
+        {
+            requirement.push_str(&format!("[{}]", extras.join(",")));
         }
-        if let Some(ref extras) = self.extras {
-            let extras = extras.iter().flat_map(|e| ["--extra", e]);
-            args.extend(extras);
+        if let Some(specifier) = self.specifier {
+            requirement.extend(specifier.chars().filter(|c| !c.is_whitespace()));
         }
-        CmdBuilder::uv(args).run()?;
-        Ok(())
+        requirement
+    }
+
+    /// Install all plugins in one `uv add` run, flags are shared by all options
+    pub fn install_all(options: &[Self], work_dir: &Path) -> Result<()> {
+        let Some(first) = options.first() else {
+            return Ok(());
+        };
+        let requirements: Vec<String> = options.iter().map(|o| o.requirement()).collect();
+        uv::add(requirements.iter().map(|r| r.as_str()).collect())
+            .upgrade(first.upgrade)
+            .reinstall(first.reinstall)
+            .editable(first.editable)
+            .index_url_opt(first.index_url)
+            .working_dir(work_dir)
+            .run()
     }
 }
 
@@ -264,120 +747,154 @@ impl PluginManager {
 
         Ok(Self {
             client,
+            config: NbrConfig::load_with_project_or_default(&work_dir),
             work_dir,
             registry_plugins,
         })
     }
 
-    pub async fn install(&mut self, options: InstallOptions<'_>, fetch_remote: bool) -> Result<()> {
-        if options.git_url.is_some() {
-            return self.install_from_github(options).await;
+    /// Install plugins, resolved together in one `uv add` run
+    pub async fn install(
+        &mut self,
+        mut options: Vec<InstallOptions<'_>>,
+        fetch_remote: bool,
+    ) -> Result<()> {
+        if options.is_empty() {
+            anyhow::bail!("No plugin specified");
+        }
+        if let Some(option) = options.iter().find(|o| o.editable && o.path.is_none()) {
+            anyhow::bail!(
+                "--editable only applies to local paths, '{}' is not a local path",
+                option.name
+            );
         }
-        if let Ok(registry_plugin) = self.get_registry_plugin(options.name, fetch_remote).await {
-            return self.install_registry_plugin(registry_plugin, options).await;
+        let adapters = self.project_adapters();
+
+        // 确定每个插件的来源和模块名，非 registry 插件安装后再解析模块名
+        let mut module_names: Vec<Option<String>> = Vec::with_capacity(options.len());
+        let mut sources = Vec::with_capacity(options.len());
+        // 安装前无法确定包名的 git 插件，安装后从 tool.uv.sources 读取
+        let mut unresolved = vec![false; options.len()];
+        for (option, unresolved) in options.iter_mut().zip(&mut unresolved) {
+            if let Some(ref git) = option.git {
+                debug!("Installing plugin from git: {}", git.requirement());
+                // 包名以仓库中的 pyproject.toml 为准，而不是仓库名
+                let spinner = terminal_utils::create_spinner("Fetching git repository...");
+                let name = git.fetch_package_name().await;
+                spinner.finish_and_clear();
+                match name {
+                    Ok(name) => option.name = name,
+                    Err(e) => {
+                        debug!("Leaving the package name to uv: {:#}", e);
+                        *unresolved = true;
+                    }
+                }
+                module_names.push(None);
+                sources.push("git");
+            } else if let Some(url) = option.url {
+                debug!("Installing plugin from url: {}", url);
+                module_names.push(None);
+                sources.push("url");
+            } else if let Some(path) = option.path {
+                debug!("Installing plugin from local path: {}", path);
+                module_names.push(Some(option.module_name.clone()));
+                sources.push(if option.editable { "editable" } else { "path" });
+            } else if let Ok(registry_plugin) =
+                self.get_registry_plugin(&option.name, fetch_remote).await
+            {
+                self.display_plugin_info(registry_plugin);
+                Self::check_adapter_compatibility(registry_plugin, &adapters, option)?;
+                module_names.push(Some(registry_plugin.module_name.clone()));
+                sources.push("registry");
+            } else {
+                debug!("Installing unregistered plugin: {}", option.name);
+                module_names.push(None);
+                sources.push("PyPI");
+            }
         }
 
-        self.install_unregistered_plugin(options).await
-    }
-
-    pub async fn install_from_github(&mut self, options: InstallOptions<'_>) -> Result<()> {
-        let git_url = options
-            .git_url
-            .context("git_url should be present if install_from_github is called")?;
-        debug!("Installing plugin from github: {}", git_url);
-
-        let prompt = StyledText::new(" ")
-            .text("Would you like to install")
-            .cyan(options.name)
-            .text("from github")
-            .to_string();
-        // 确定是否安装 github 插件
-        if Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(prompt)
-            .default(true)
-            .interact()?
-        {
-            options.install()?;
+        info!("Plugins to install:");
+        for (option, source) in options.iter().zip(&sources) {
+            StyledText::new(" ")
+                .text(" ")
+                .cyan(option.requirement())
+                .text(format!("({source})"))
+                .println();
+        }
+        let prompt = if options.len() == 1 {
+            StyledText::new(" ")
+                .text("Would you like to install")
+                .cyan(&options[0].name)
+                .to_string()
         } else {
-            error!("{}", "Installation operation cancelled.");
+            format!("Would you like to install these {} plugins", options.len())
+        };
+        if !Confirm::new(prompt).default(true).interact()? {
+            error!("Installation operation cancelled.");
             return Ok(());
         }
 
-        // Add to configuration
-        NbTomlEditor::with_work_dir(Some(&self.work_dir))?
-            .add_plugins(vec![&options.module_name])?;
-
-        StyledText::new(" ")
-            .green_bold("✓ Successfully installed plugin:")
-            .cyan_bold(options.name)
-            .println();
-        Ok(())
-    }
-
-    pub async fn install_unregistered_plugin(&mut self, options: InstallOptions<'_>) -> Result<()> {
-        debug!("Installing unregistered plugin: {}", options.name);
-
-        let prompt = StyledText::new(" ")
-            .text("Would you like to install")
-            .cyan(options.name)
-            .text("from PyPI?")
-            .to_string();
-        if Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(prompt)
-            .default(true)
-            .interact()?
-        {
-            options.install()?;
-        } else {
-            error!("{}", "Installation operation cancelled.");
-            return Ok(());
+        InstallOptions::install_all(&options, &self.work_dir)?;
+        for (option, _) in options.iter_mut().zip(&unresolved).filter(|(_, u)| **u) {
+            if let Some(name) = option
+                .git
+                .as_ref()
+                .and_then(|git| git.installed_name(&self.work_dir))
+            {
+                option.name = name;
+            }
         }
+        let module_names: Vec<String> = module_names
+            .into_iter()
+            .zip(&options)
+            .map(|(module_name, option)| {
+                module_name.unwrap_or_else(|| dist::module_name(&self.work_dir, &option.name))
+            })
+            .collect();
 
         // Add to configuration
         NbTomlEditor::with_work_dir(Some(&self.work_dir))?
-            .add_plugins(vec![&options.module_name])?;
+            .add_plugins(module_names.iter().map(|m| m.as_str()).collect())?;
 
         StyledText::new(" ")
-            .green_bold("✓ Successfully installed plugin:")
-            .cyan_bold(options.name)
+            .green_bold("✓ Successfully installed plugin(s):")
+            .cyan_bold(
+                options
+                    .iter()
+                    .map(|o| o.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
             .println();
         Ok(())
     }
 
-    /// Install a plugin
-    pub async fn install_registry_plugin(
-        &self,
+    /// Refuse (or warn with `--allow-incompatible`) when the plugin supports none of the configured adapters
+    fn check_adapter_compatibility(
         registry_plugin: &RegistryPlugin,
-        options: InstallOptions<'_>,
+        adapters: &[String],
+        options: &InstallOptions<'_>,
     ) -> Result<()> {
-        let package_name = &registry_plugin.project_link;
-        // Show plugin information if available
-        self.display_plugin_info(registry_plugin);
-
-        let prompt = StyledText::new(" ")
-            .text("Would you like to install")
-            .cyan(package_name)
-            .to_string();
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(prompt)
-            .default(true)
-            .interact()?
-        {
-            error!("Installation operation cancelled.");
+        if registry_plugin.is_compatible_with(adapters) {
             return Ok(());
         }
-        // Install the plugin
-        options.install()?;
-
-        // Add to configuration
-        NbTomlEditor::with_work_dir(Some(&self.work_dir))?
-            .add_plugins(vec![&registry_plugin.module_name])?;
-
-        StyledText::new(" ")
-            .green_bold("✓ Successfully installed plugin:")
-            .cyan_bold(package_name)
-            .println();
-
+        let supported = registry_plugin
+            .supported_adapters
+            .as_deref()
+            .unwrap_or_default()
+            .join(", ");
+        if !options.allow_incompatible {
+            anyhow::bail!(
+                "Plugin '{}' only supports adapters [{}], none of them is in tool.nonebot.adapters. \
+                Use --allow-incompatible to install it anyway.",
+                registry_plugin.project_link,
+                supported
+            );
+        }
+        warn!(
+            "Plugin '{}' only supports adapters [{}], none of them is in tool.nonebot.adapters.",
+            registry_plugin.project_link, supported
+        );
         Ok(())
     }
 
@@ -399,21 +916,25 @@ impl PluginManager {
             anyhow::bail!("Plugin '{}' is not installed.", package_name);
         }
 
-        if Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!("Would you like to uninstall '{package_name}'",))
+        if Confirm::new(format!("Would you like to uninstall '{package_name}'"))
             .default(false)
             .interact()?
         {
+            // 卸载前读取模块名和孤立插件，卸载后 dist-info 已不存在
+            let module_name = dist::module_name(&self.work_dir, package_name);
+            let orphans = self.find_orphans(package_name).await;
             uv::remove(vec![&package_name])
                 .working_dir(&self.work_dir)
                 .run()?;
             NbTomlEditor::with_work_dir(Some(&self.work_dir))?
-                .remove_plugins(vec![&package_name.replace("-", "_")])?;
+                .remove_plugins(vec![&module_name])?;
 
             StyledText::new(" ")
                 .green_bold("✓ Successfully uninstalled plugin:")
                 .cyan_bold(package_name)
                 .println();
+
+            self.remove_orphans(&orphans, package_name);
         } else {
             error!("Uninstallation operation cancelled.");
             return Ok(());
@@ -432,8 +953,7 @@ impl PluginManager {
             );
         }
         // Confirm uninstallation
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!("Would you like to uninstall '{package_name}'"))
+        if !Confirm::new(format!("Would you like to uninstall '{package_name}'"))
             .default(false)
             .interact()?
         {
@@ -441,8 +961,13 @@ impl PluginManager {
             return Ok(());
         }
 
+        // 卸载前查找孤立插件，卸载后 dist-info 已不存在
+        let orphans = self.find_orphans(&package_name).await;
+
         // Uninstall the package
-        uv::remove(vec![&package_name]).run()?;
+        uv::remove(vec![&package_name])
+            .working_dir(&self.work_dir)
+            .run()?;
 
         NbTomlEditor::with_work_dir(Some(&self.work_dir))?
             .remove_plugins(vec![&registry_plugin.module_name])?;
@@ -452,67 +977,839 @@ impl PluginManager {
             .cyan_bold(&package_name)
             .println();
 
+        self.remove_orphans(&orphans, &package_name);
         Ok(())
     }
 
-    pub async fn get_installed_plugins(&self, outdated: bool) -> Result<Vec<Package>> {
-        let installed_packages = uv::list(outdated).await?;
-        let installed_plugins = installed_packages
+    /// Plugins only required by `removed`, resolved before it is uninstalled
+    ///
+    /// The other plugins in tool.nonebot.plugins and the packages in project.dependencies
+    /// are kept, an empty list is returned if the dependency graph can not be resolved.
+    async fn find_orphans(&self, removed: &str) -> Vec<Orphan> {
+        let graph = match self.dependency_graph().await {
+            Ok(graph) => graph,
+            Err(e) => {
+                debug!("Skipping orphan cleanup: {:#}", e);
+                return Vec::new();
+            }
+        };
+        let removed = dist::normalize_name(removed);
+        let Some(removed_node) = graph
+            .nodes()
+            .find(|node| dist::normalize_name(node) == removed)
+        else {
+            return Vec::new();
+        };
+
+        let direct = self.direct_dependencies();
+        let mut roots = self.declared_packages(&graph);
+        roots.extend(
+            graph
+                .nodes()
+                .filter(|node| direct.contains(&dist::normalize_name(node))),
+        );
+        roots.retain(|node| *node != removed_node);
+
+        graph
+            .orphans(removed_node, &roots)
             .into_iter()
-            .filter(|p| Self::is_plugin(&p.name))
-            .collect();
-        Ok(installed_plugins)
+            .filter(|node| !node.starts_with("nonebot-adapter-"))
+            .map(|package| Orphan {
+                module: dist::module_name(&self.work_dir, &package),
+                package,
+            })
+            .collect()
     }
 
-    pub async fn list(&self, show_outdated: bool) -> Result<()> {
-        // 获取所有插件
-        let mut installed_plugins = self.get_installed_plugins(false).await?;
-        // 获取需要更新的插件
-        if show_outdated {
-            let outdated_plugins = self.get_installed_plugins(true).await?;
-            // 去重，保留 outdated 的包
-            installed_plugins.retain(|p| !outdated_plugins.contains(p));
-            installed_plugins.extend(outdated_plugins);
+    /// Normalized package names in project.dependencies
+    fn direct_dependencies(&self) -> HashSet<String> {
+        PyProjectConfig::parse(Some(&self.work_dir))
+            .map(|config| {
+                config
+                    .project
+                    .dependencies
+                    .iter()
+                    .map(|dependency| dist::normalize_name(dist::requirement_name(dependency)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Offer to remove the plugins only required by the removed package
+    ///
+    /// The plugin itself is already uninstalled, so a failure here is only reported.
+    fn remove_orphans(&self, orphans: &[Orphan], removed: &str) {
+        if orphans.is_empty() {
+            return;
+        }
+        if let Err(e) = self.try_remove_orphans(orphans, removed) {
+            warn!("Failed to remove orphaned plugins: {:#}", e);
         }
+    }
 
-        if installed_plugins.is_empty() {
-            warn!("No plugins installed.");
+    fn try_remove_orphans(&self, orphans: &[Orphan], removed: &str) -> Result<()> {
+        let packages: Vec<&str> = orphans.iter().map(|o| o.package.as_str()).collect();
+        let selected = MultiSelect::new(
+            format!("These plugins were only required by {removed}, select plugin(s) to remove"),
+            &packages,
+        )
+        .defaults(&vec![true; orphans.len()])
+        .interact()?;
+        if selected.is_empty() {
             return Ok(());
         }
+        let selected: Vec<&Orphan> = selected.into_iter().map(|i| &orphans[i]).collect();
 
-        info!("Installed Plugins:");
-        installed_plugins.iter().for_each(|p| p.display_info());
+        // 传递依赖已随 uv remove 卸载，只有直接依赖需要再次 uv remove
+        let direct = self.direct_dependencies();
+        let direct_packages: Vec<&str> = selected
+            .iter()
+            .filter(|o| direct.contains(&dist::normalize_name(&o.package)))
+            .map(|o| o.package.as_str())
+            .collect();
+        if !direct_packages.is_empty() {
+            uv::remove(direct_packages)
+                .working_dir(&self.work_dir)
+                .run()?;
+        }
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?
+            .remove_plugins(selected.iter().map(|o| o.module.as_str()).collect())?;
 
+        StyledText::new(" ")
+            .green_bold("✓ Successfully removed orphaned plugin(s):")
+            .cyan_bold(
+                selected
+                    .iter()
+                    .map(|o| o.package.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .println();
         Ok(())
     }
 
-    pub fn is_plugin(package_name: &str) -> bool {
-        package_name.starts_with("nonebot") && package_name.contains("plugin")
+    pub async fn get_installed_plugins(&self, outdated: bool) -> Result<Vec<Package>> {
+        let installed_packages = uv::list(outdated).await?;
+        Ok(self.filter_plugins(installed_packages).await)
     }
 
-    pub async fn reset(&self) -> Result<()> {
-        let mut installed_plugins = self.get_installed_plugins(false).await?;
+    /// Keep the packages that are NoneBot plugins
+    ///
+    /// Packages published to the registry are plugins, other packages are checked
+    /// by their installed metadata.
+    pub async fn filter_plugins(&self, packages: Vec<Package>) -> Vec<Package> {
+        let registry_packages = self.registry_package_names().await;
+        packages
+            .into_iter()
+            .filter(|p| self.is_plugin_package(&p.name, &registry_packages))
+            .collect()
+    }
 
-        let mut requires_plugins: Vec<String> = Vec::new();
-        for plugin in &installed_plugins {
-            let requires = uv::show_package_info(plugin.name.as_str(), Some(&self.work_dir))
-                .await?
-                .requires
-                .unwrap_or_default();
-            for require in requires {
-                if Self::is_plugin(&require) && !requires_plugins.contains(&require) {
-                    requires_plugins.push(require);
-                }
+    /// Normalized package names of registry plugins, empty if the registry is unavailable
+    async fn registry_package_names(&self) -> HashSet<String> {
+        match self.fetch_registry_plugins(false).await {
+            Ok(plugins) => plugins.keys().map(|k| dist::normalize_name(k)).collect(),
+            Err(e) => {
+                debug!("Detecting plugins without registry: {:#}", e);
+                HashSet::new()
             }
         }
+    }
 
-        // 去除 requires 的插件
-        installed_plugins.retain(|p| !requires_plugins.contains(&p.name));
+    fn is_plugin_package(&self, package_name: &str, registry_packages: &HashSet<String>) -> bool {
+        if registry_packages.contains(&dist::normalize_name(package_name)) {
+            return true;
+        }
+        match Distribution::find(&self.work_dir, package_name) {
+            Some(distribution) => distribution.is_nonebot_plugin(),
+            // 找不到虚拟环境时退回到名称判断
+            None => Self::is_plugin(package_name),
+        }
+    }
 
-        let plugins = installed_plugins
+    /// Installed plugins with a newer version
+    ///
+    /// Versions are compared with the cached registry, which also works offline. With
+    /// `pypi` set, `uv pip list --outdated` is used and the registry is the fallback.
+    pub async fn outdated_plugins(&self, pypi: bool) -> Result<Vec<Package>> {
+        if pypi {
+            match self.get_installed_plugins(true).await {
+                Ok(plugins) => return Ok(plugins),
+                Err(e) => warn!("Failed to check PyPI, using the registry instead: {:#}", e),
+            }
+        }
+        let installed_plugins = self.get_installed_plugins(false).await?;
+        let latest_versions = self
+            .fetch_registry_plugins(false)
+            .await?
+            .values()
+            .map(|p| (dist::normalize_name(&p.project_link), p.version.clone()))
+            .collect();
+        Ok(registry::outdated_packages(
+            installed_plugins,
+            &latest_versions,
+        ))
+    }
+
+    pub async fn list(&self, show_outdated: bool, pypi: bool, fix: bool) -> Result<()> {
+        // 获取所有插件
+        let mut installed_plugins = self.get_installed_plugins(false).await?;
+        // 获取需要更新的插件
+        if show_outdated {
+            let outdated_plugins = self.outdated_plugins(pypi).await?;
+            // 去重，保留 outdated 的包
+            installed_plugins.retain(|p| !outdated_plugins.contains(p));
+            installed_plugins.extend(outdated_plugins);
+        }
+
+        let declared = self.declared_plugins();
+        let disabled = self.disabled_plugins();
+        // 只有存在未加载的插件时才需要依赖关系来区分
+        let required = if installed_plugins.iter().any(|p| {
+            let module = dist::module_name(&self.work_dir, &p.name);
+            !declared.contains(&module) && !disabled.contains(&module)
+        }) {
+            self.required_plugins().await
+        } else {
+            HashSet::new()
+        };
+        let statuses =
+            reconcile_plugins(&declared, &disabled, &required, installed_plugins, |name| {
+                dist::module_name(&self.work_dir, name)
+            });
+        if statuses.is_empty() {
+            warn!("No plugins installed.");
+            return Ok(());
+        }
+
+        // 标记不支持当前适配器的插件，registry 不可用时跳过检查
+        let adapters = self.project_adapters();
+        let registry_plugins = match self.fetch_registry_plugins(false).await {
+            Ok(plugins) => Some(plugins),
+            Err(e) => {
+                debug!("Skipping adapter compatibility check: {:#}", e);
+                None
+            }
+        };
+
+        info!("Installed Plugins:");
+        for status in &statuses {
+            status.display();
+            if let Some(ref plugin) = status.package
+                && let Some(direct_url) =
+                    Distribution::find(&self.work_dir, &plugin.name).and_then(|d| d.direct_url())
+                && let Some(path) = direct_url.local_path()
+            {
+                StyledText::new(" ")
+                    .text("   ")
+                    .blue(if direct_url.is_editable() {
+                        "↳ Editable install from"
+                    } else {
+                        "↳ Installed from"
+                    })
+                    .white(path.display().to_string())
+                    .println();
+            }
+            if let Some(ref plugin) = status.package
+                && let Some(registry_plugin) = registry_plugins.and_then(|p| p.get(&plugin.name))
+                && !registry_plugin.is_compatible_with(&adapters)
+            {
+                StyledText::new(" ")
+                    .text("   ")
+                    .yellow("⚠ Incompatible with configured adapters, supports:")
+                    .white(
+                        registry_plugin
+                            .supported_adapters
+                            .as_deref()
+                            .unwrap_or_default()
+                            .join(", "),
+                    )
+                    .println();
+            }
+        }
+
+        if fix {
+            self.fix_plugins(&statuses, registry_plugins)?;
+        }
+
+        Ok(())
+    }
+
+    /// List the plugin modules and packages found in tool.nonebot.plugin_dirs
+    ///
+    /// Falls back to `src/plugins` like `plugin create` when no directory is configured.
+    pub fn list_local(&self) -> Result<()> {
+        let mut plugin_dirs = self.plugin_dirs();
+        if plugin_dirs.is_empty() {
+            debug!(
+                "No plugin directory configured, using {}",
+                DEFAULT_PLUGIN_DIR
+            );
+            plugin_dirs.push(DEFAULT_PLUGIN_DIR.to_string());
+        }
+        for dir in &plugin_dirs {
+            if !self.work_dir.join(dir).is_dir() {
+                warn!("Plugin directory '{}' does not exist.", dir);
+            }
+        }
+
+        let local_plugins = find_local_plugins(&self.work_dir, &plugin_dirs);
+        if local_plugins.is_empty() {
+            warn!("No local plugins found.");
+            return Ok(());
+        }
+        info!("Local Plugins:");
+        for plugin in &local_plugins {
+            StyledText::new(" ")
+                .text(" ")
+                .cyan(&plugin.module_name)
+                .with(|text| {
+                    if let Some(ref name) = plugin.name {
+                        text.green(format!("({name})"));
+                    }
+                })
+                .white(plugin.path.display().to_string())
+                .println();
+        }
+        Ok(())
+    }
+
+    /// Directories in tool.nonebot.plugin_dirs
+    fn plugin_dirs(&self) -> Vec<String> {
+        PyProjectConfig::parse(Some(&self.work_dir))
+            .ok()
+            .and_then(|config| config.nonebot()?.plugin_dirs.clone())
+            .unwrap_or_default()
+    }
+
+    /// Add directories to tool.nonebot.plugin_dirs, offering to create missing ones
+    pub fn add_plugin_dirs(&self, dirs: &[String]) -> Result<()> {
+        let dirs: Vec<&str> = dirs.iter().map(|dir| normalize_plugin_dir(dir)).collect();
+        for dir in &dirs {
+            let path = self.work_dir.join(dir);
+            if !path.is_dir()
+                && Confirm::new(format!("Directory '{dir}' does not exist, create it"))
+                    .default(true)
+                    .interact()?
+            {
+                fs::create_dir_all(&path)
+                    .with_context(|| format!("Failed to create directory: {}", path.display()))?;
+            }
+        }
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?.add_plugin_dirs(dirs.clone())?;
+
+        StyledText::new(" ")
+            .green_bold("✓ Successfully added plugin dir(s):")
+            .cyan_bold(dirs.join(", "))
+            .println();
+        Ok(())
+    }
+
+    /// Remove directories from tool.nonebot.plugin_dirs, the files are kept
+    pub fn remove_plugin_dirs(&self, dirs: &[String]) -> Result<()> {
+        let plugin_dirs = self.plugin_dirs();
+        let (dirs, unknown): (Vec<&str>, Vec<&str>) = dirs
+            .iter()
+            .map(|dir| normalize_plugin_dir(dir))
+            .partition(|dir| plugin_dirs.iter().any(|d| d == dir));
+        for dir in &unknown {
+            warn!("'{}' is not in tool.nonebot.plugin_dirs", dir);
+        }
+        if dirs.is_empty() {
+            return Ok(());
+        }
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?.remove_plugin_dirs(dirs.clone())?;
+
+        StyledText::new(" ")
+            .green_bold("✓ Successfully removed plugin dir(s):")
+            .cyan_bold(dirs.join(", "))
+            .println();
+        Ok(())
+    }
+
+    /// Install declared-but-missing plugins and register installed-but-unloaded plugins
+    fn fix_plugins(
+        &self,
+        statuses: &[PluginStatus],
+        registry_plugins: Option<&HashMap<String, RegistryPlugin>>,
+    ) -> Result<()> {
+        let missing: Vec<&str> = statuses
+            .iter()
+            .filter(|s| s.state == PluginState::Missing)
+            .map(|s| s.module_name.as_str())
+            .collect();
+        let unloaded: Vec<&str> = statuses
+            .iter()
+            .filter(|s| s.state == PluginState::Unloaded)
+            .map(|s| s.module_name.as_str())
+            .collect();
+        if missing.is_empty() && unloaded.is_empty() {
+            info!("Installed plugins are in sync with tool.nonebot.plugins.");
+            return Ok(());
+        }
+
+        if !missing.is_empty()
+            && Confirm::new(format!(
+                "Would you like to install {} missing plugin(s): {}",
+                missing.len(),
+                missing.join(", ")
+            ))
+            .default(true)
+            .interact()?
+        {
+            // registry 中找不到时按模块名推断包名
+            let package_names: Vec<String> = missing
+                .iter()
+                .map(|module| {
+                    registry_plugins
+                        .and_then(|plugins| plugins.values().find(|p| p.module_name == *module))
+                        .map(|p| p.project_link.clone())
+                        .unwrap_or_else(|| module.replace("_", "-"))
+                })
+                .collect();
+            uv::add(package_names.iter().map(|p| p.as_str()).collect())
+                .working_dir(&self.work_dir)
+                .run()?;
+            StyledText::new(" ")
+                .green_bold("✓ Successfully installed plugin(s):")
+                .cyan_bold(package_names.join(", "))
+                .println();
+        }
+
+        if !unloaded.is_empty() {
+            let selected = MultiSelect::new(
+                "Select plugin(s) to register in tool.nonebot.plugins",
+                &unloaded,
+            )
+            .defaults(&vec![true; unloaded.len()])
+            .interact()?;
+            let selected: Vec<&str> = selected.into_iter().map(|i| unloaded[i]).collect();
+            if !selected.is_empty() {
+                NbTomlEditor::with_work_dir(Some(&self.work_dir))?.add_plugins(selected.clone())?;
+                StyledText::new(" ")
+                    .green_bold("✓ Successfully registered plugin(s):")
+                    .cyan_bold(selected.join(", "))
+                    .println();
+            }
+        }
+        Ok(())
+    }
+
+    /// Normalized names of the plugins required by other plugins, as excluded by `reset`
+    async fn required_plugins(&self) -> HashSet<String> {
+        match self.dependency_graph().await {
+            Ok(graph) => graph
+                .dependencies()
+                .into_iter()
+                .map(dist::normalize_name)
+                .collect(),
+            Err(e) => {
+                debug!("Skipping plugin dependency detection: {:#}", e);
+                HashSet::new()
+            }
+        }
+    }
+
+    /// Plugin modules in tool.nonebot.plugins
+    fn declared_plugins(&self) -> Vec<String> {
+        PyProjectConfig::parse(Some(&self.work_dir))
+            .ok()
+            .and_then(|config| config.nonebot()?.plugins.clone())
+            .unwrap_or_default()
+    }
+
+    /// Plugin modules in tool.nbr.disabled-plugins
+    fn disabled_plugins(&self) -> Vec<String> {
+        ProjectConfig::parse(&self.work_dir)
+            .ok()
+            .flatten()
+            .map(|config| config.disabled_plugins)
+            .unwrap_or_default()
+    }
+
+    /// The module in `modules` matching a package name or module name
+    fn find_module(&self, name: &str, modules: &[String]) -> Option<String> {
+        [name.to_string(), dist::module_name(&self.work_dir, name)]
+            .into_iter()
+            .find(|module| modules.contains(module))
+    }
+
+    /// Introspect the plugin's `PluginMetadata.config` with the project's Python
+    pub async fn config(&self, name: &str, write: bool) -> Result<()> {
+        let module_name = self
+            .find_module(name, &self.declared_plugins())
+            .unwrap_or_else(|| dist::module_name(&self.work_dir, name));
+        let python = find_python_executable(&self.work_dir)?;
+        let plugin_dirs: Vec<String> = self
+            .plugin_dirs()
+            .iter()
+            .map(|dir| self.work_dir.join(dir).to_string_lossy().to_string())
+            .collect();
+        let mut args = vec!["-c", PLUGIN_CONFIG_SCRIPT, module_name.as_str()];
+        args.extend(plugin_dirs.iter().map(|dir| dir.as_str()));
+
+        let spinner = terminal_utils::create_spinner(format!("Loading plugin {module_name}..."));
+        let output =
+            process_utils::execute_command_with_output(&python, &args, Some(&self.work_dir), 60)
+                .await;
+        spinner.finish_and_clear();
+        let stdout = String::from_utf8_lossy(&output?.stdout).to_string();
+        let config: PluginConfig = stdout
+            .lines()
+            .find_map(|line| line.strip_prefix("__NBR_CONFIG__"))
+            .context("Failed to read the plugin config from python output")
+            .and_then(|json| serde_json::from_str(json).context("Invalid plugin config output"))?;
+
+        if !config.config {
+            warn!(
+                "Plugin '{}' has no config model in __plugin_meta__.",
+                module_name
+            );
+            return Ok(());
+        }
+        if config.fields.is_empty() {
+            warn!("The config model of '{}' has no fields.", module_name);
+            return Ok(());
+        }
+        info!("Config of {}:", module_name);
+        for field in &config.fields {
+            StyledText::new(" ")
+                .text(" ")
+                .cyan(field.env_key())
+                .purple(&field.type_name)
+                .with(|text| match field.default {
+                    Some(ref default) => {
+                        text.text("=").green(default);
+                    }
+                    None => {
+                        text.red("(required)");
+                    }
+                })
+                .with(|text| {
+                    if let Some(ref description) = field.description {
+                        text.white(description);
+                    }
+                })
+                .println();
+        }
+
+        if write {
+            let env_file = active_env_file(&self.work_dir);
+            let existing = fs::read_to_string(&env_file).unwrap_or_default();
+            let entries = env_entries(&module_name, &config.fields, &existing);
+            if entries.is_empty() {
+                info!("All fields are already in {}.", env_file.display());
+                return Ok(());
+            }
+            let mut content = existing;
+            if !content.is_empty() && !content.ends_with('\n') {
+                content.push('\n');
+            }
+            content.push_str(&entries);
+            fs::write(&env_file, content)
+                .with_context(|| format!("Failed to write {}", env_file.display()))?;
+            StyledText::new(" ")
+                .green_bold("✓ Successfully appended config entries to")
+                .cyan_bold(env_file.display().to_string())
+                .println();
+        }
+        Ok(())
+    }
+
+    /// Move a plugin from tool.nonebot.plugins to tool.nbr.disabled-plugins, the package stays installed
+    pub fn disable(&self, name: &str) -> Result<()> {
+        if let Some(module_name) = self.find_module(name, &self.disabled_plugins()) {
+            warn!("Plugin '{}' is already disabled.", module_name);
+            return Ok(());
+        }
+        let module_name = self
+            .find_module(name, &self.declared_plugins())
+            .with_context(|| format!("Plugin '{}' is not in tool.nonebot.plugins", name))?;
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?.disable_plugins(vec![&module_name])?;
+
+        StyledText::new(" ")
+            .green_bold("✓ Successfully disabled plugin:")
+            .cyan_bold(&module_name)
+            .println();
+        Ok(())
+    }
+
+    /// Move a plugin from tool.nbr.disabled-plugins back to tool.nonebot.plugins
+    pub fn enable(&self, name: &str) -> Result<()> {
+        let module_name = self
+            .find_module(name, &self.disabled_plugins())
+            .with_context(|| format!("Plugin '{}' is not disabled", name))?;
+        NbTomlEditor::with_work_dir(Some(&self.work_dir))?.enable_plugins(vec![&module_name])?;
+
+        StyledText::new(" ")
+            .green_bold("✓ Successfully enabled plugin:")
+            .cyan_bold(&module_name)
+            .println();
+        Ok(())
+    }
+
+    /// Adapter modules in tool.nonebot.adapters
+    fn project_adapters(&self) -> Vec<String> {
+        PyProjectConfig::parse(Some(&self.work_dir))
+            .ok()
+            .and_then(|config| config.nonebot()?.adapters.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|adapter| adapter.module_name)
+            .collect()
+    }
+
+    /// Show plugin details, combining registry record, installed package and pyproject state
+    pub async fn info(&self, name: &str, fetch_remote: bool) -> Result<()> {
+        let registry_plugin = match self.fetch_registry_plugins(fetch_remote).await {
+            Ok(plugins) => plugins
+                .get(name)
+                .or_else(|| plugins.values().find(|p| p.module_name == name)),
+            Err(e) => {
+                warn!("Failed to fetch registry plugins: {:#}", e);
+                None
+            }
+        };
+        let package_name = registry_plugin
+            .map(|p| p.project_link.clone())
+            .unwrap_or_else(|| name.replace("_", "-"));
+        let module_name = registry_plugin
+            .map(|p| p.module_name.clone())
+            .unwrap_or_else(|| dist::module_name(&self.work_dir, &package_name));
+
+        let installed = uv::show_package_info(&package_name, Some(&self.work_dir))
+            .await
+            .ok();
+        if registry_plugin.is_none() && installed.is_none() {
+            anyhow::bail!("Plugin '{}' not found in registry or environment", name);
+        }
+        // 是否已在 tool.nonebot.plugins 中启用
+        let configured = PyProjectConfig::parse(Some(&self.work_dir))
+            .ok()
+            .and_then(|config| config.nonebot()?.plugins.clone())
+            .is_some_and(|plugins| plugins.contains(&module_name));
+
+        match registry_plugin {
+            Some(plugin) => self.display_plugin_details(plugin),
+            None => {
+                StyledText::new("").cyan_bold(&package_name).println();
+                StyledText::new(" ")
+                    .text("  Registry:")
+                    .yellow("not published to registry")
+                    .println();
+            }
+        }
+
+        StyledText::new(" ")
+            .text("  Installed:")
+            .with(|text| match installed {
+                Some(ref package) => {
+                    text.green(format!("v{}", package.version));
+                }
+                None => {
+                    text.red("not installed");
+                }
+            })
+            .println();
+        StyledText::new(" ")
+            .text("  In pyproject:")
+            .with(|text| {
+                if configured {
+                    text.green(format!("{} in tool.nonebot.plugins", module_name));
+                } else {
+                    text.yellow(format!("{} not in tool.nonebot.plugins", module_name));
+                }
+            })
+            .println();
+        if let Some(requires_by) = installed
+            .and_then(|package| package.requires_by)
+            .filter(|requires_by| !requires_by.is_empty())
+        {
+            StyledText::new(" ")
+                .text("  Required by:")
+                .white(requires_by.join(", "))
+                .println();
+        }
+
+        Ok(())
+    }
+
+    /// Build the dependency graph of installed plugins and adapters
+    pub async fn dependency_graph(&self) -> Result<DependencyGraph> {
+        let packages = uv::list(false).await?;
+        let mut nodes: Vec<String> = packages
             .iter()
-            .map(|p| p.name.replace("-", "_"))
-            .collect::<Vec<String>>();
+            .filter(|p| p.name.starts_with("nonebot-adapter-"))
+            .map(|p| p.name.clone())
+            .collect();
+        nodes.extend(
+            self.filter_plugins(packages)
+                .await
+                .into_iter()
+                .map(|p| p.name),
+        );
+        let node_names: HashMap<String, &str> = nodes
+            .iter()
+            .map(|n| (dist::normalize_name(n), n.as_str()))
+            .collect();
+
+        let spinner = terminal_utils::create_spinner("Resolving plugin dependencies...");
+        let mut graph = DependencyGraph::default();
+        for node in &nodes {
+            graph.add_node(node);
+            // 优先读取已安装的元数据，找不到虚拟环境时才调用 uv
+            let requires = match Distribution::find(&self.work_dir, node) {
+                Some(distribution) => distribution.requires(),
+                None => uv::show_package_info(node, Some(&self.work_dir))
+                    .await?
+                    .requires
+                    .unwrap_or_default(),
+            };
+            for require in requires {
+                if let Some(dependency) = node_names.get(&dist::normalize_name(&require)) {
+                    graph.add_edge(node, dependency);
+                }
+            }
+        }
+        spinner.finish_and_clear();
+        Ok(graph)
+    }
+
+    /// Show the plugin dependency tree
+    ///
+    /// Roots are the plugins in tool.nonebot.plugins, or with `--invert` the plugins
+    /// required by others.
+    pub async fn tree(&self, name: Option<&str>, invert: bool, format: &TreeFormat) -> Result<()> {
+        let graph = self.dependency_graph().await?;
+        let roots: Vec<String> = match name {
+            Some(name) => {
+                let package = dist::normalize_name(name);
+                let node = graph
+                    .nodes()
+                    .find(|node| {
+                        dist::normalize_name(node) == package
+                            || dist::module_name(&self.work_dir, node) == name
+                    })
+                    .with_context(|| format!("Plugin '{}' is not installed", name))?;
+                vec![node.to_string()]
+            }
+            None if invert => graph
+                .dependencies()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            None => self
+                .declared_packages(&graph)
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        };
+        let (graph, key) = if invert {
+            (graph.inverted(), "required_by")
+        } else {
+            (graph, "dependencies")
+        };
+
+        match format {
+            TreeFormat::Text => {
+                if roots.is_empty() {
+                    warn!("No plugins to show.");
+                    return Ok(());
+                }
+                print!("{}", graph.render_tree(&roots));
+            }
+            TreeFormat::Dot => print!("{}", graph.reachable(&roots).to_dot("plugins")),
+            TreeFormat::Json => println!(
+                "{}",
+                serde_json::to_string_pretty(&graph.to_json(&roots, key))?
+            ),
+        }
+        Ok(())
+    }
+
+    /// Installed packages of the plugins in tool.nonebot.plugins, in declared order
+    fn declared_packages<'g>(&self, graph: &'g DependencyGraph) -> Vec<&'g str> {
+        let installed: Vec<(&str, String)> = graph
+            .nodes()
+            .map(|node| (node, dist::module_name(&self.work_dir, node)))
+            .collect();
+        self.declared_plugins()
+            .iter()
+            .filter_map(|module| {
+                installed
+                    .iter()
+                    .find(|(_, m)| m == module)
+                    .map(|(node, _)| *node)
+            })
+            .collect()
+    }
+
+    /// Guess from the package name, only used when the metadata is unavailable
+    pub fn is_plugin(package_name: &str) -> bool {
+        package_name.starts_with("nonebot") && package_name.contains("plugin")
+    }
+
+    /// Rebuild tool.nonebot.plugins from the environment
+    ///
+    /// Installed plugins are added unless they are only dependencies of other plugins,
+    /// declared plugins are kept while installed, the changes are confirmed before writing.
+    pub async fn reset(&self) -> Result<()> {
+        let graph = self.dependency_graph().await?;
+        let requires_plugins = graph.dependencies();
+
+        let declared = self.declared_plugins();
+        let disabled = self.disabled_plugins();
+        // 禁用的插件不加回 tool.nonebot.plugins
+        let installed_modules: Vec<(String, bool)> = graph
+            .nodes()
+            .filter(|node| !node.starts_with("nonebot-adapter-"))
+            .map(|node| {
+                (
+                    dist::module_name(&self.work_dir, node),
+                    requires_plugins.contains(&node),
+                )
+            })
+            .filter(|(module, _)| !disabled.contains(module))
+            .collect();
+        // 保留已声明且已安装的插件，去除 requires 的插件
+        let mut plugins: Vec<String> = declared
+            .iter()
+            .filter(|module| installed_modules.iter().any(|(m, _)| m == *module))
+            .cloned()
+            .collect();
+        for (module, required) in &installed_modules {
+            if !required && !plugins.contains(module) {
+                plugins.push(module.clone());
+            }
+        }
+
+        let added: Vec<&str> = plugins
+            .iter()
+            .filter(|p| !declared.contains(p))
+            .map(|p| p.as_str())
+            .collect();
+        let removed: Vec<&str> = declared
+            .iter()
+            .filter(|p| !plugins.contains(p))
+            .map(|p| p.as_str())
+            .collect();
+        if added.is_empty() && removed.is_empty() {
+            info!("tool.nonebot.plugins is already up to date.");
+            return Ok(());
+        }
+        for module in &added {
+            StyledText::new(" ").green("  +").text(*module).println();
+        }
+        for module in &removed {
+            StyledText::new(" ").red("  -").text(*module).println();
+        }
+        if !Confirm::new("Would you like to apply these changes to tool.nonebot.plugins")
+            .default(true)
+            .interact()?
+        {
+            error!("Reset operation cancelled.");
+            return Ok(());
+        }
 
         NbTomlEditor::with_work_dir(Some(&self.work_dir))?
             .reset_plugins(plugins.iter().map(|p| p.as_str()).collect())?;
@@ -528,12 +1825,13 @@ impl PluginManager {
         &self,
         query: &str,
         limit: usize,
+        filter: &SearchFilter,
         fetch_remote: bool,
     ) -> Result<()> {
         debug!("Searching plugins for: {}", query);
 
         let results = self
-            .search_registry_plugins(query, limit, fetch_remote)
+            .search_registry_plugins(query, limit, filter, fetch_remote)
             .await?;
 
         if results.is_empty() {
@@ -544,13 +1842,9 @@ impl PluginManager {
         info!("Found {} plugin(s):", results.len());
 
         for (index, result) in results.iter().enumerate() {
-            if index >= limit {
-                break;
-            }
-
             self.display_search_result(result, index + 1);
 
-            if index < results.len() - 1 && index < limit - 1 {
+            if index < results.len() - 1 {
                 println!();
             }
         }
@@ -563,12 +1857,18 @@ impl PluginManager {
         &mut self,
         plugin_name: Option<&str>,
         update_all: bool,
+        pypi: bool,
         reinstall: bool,
+        dry_run: bool,
     ) -> Result<()> {
         if update_all {
-            self.update_all_plugins().await?;
+            self.update_all_plugins(pypi, dry_run).await?;
         } else if let Some(name) = plugin_name {
-            self.update_single_plugin(name, reinstall)?;
+            if dry_run {
+                self.preview_upgrade(&[name]).await?;
+            } else {
+                self.update_single_plugin(name, reinstall)?;
+            }
         } else {
             anyhow::bail!("Either specify a plugin name or use --all( -a) flag",);
         }
@@ -576,32 +1876,45 @@ impl PluginManager {
     }
 
     /// Update all plugins
-    async fn update_all_plugins(&self) -> Result<()> {
-        let outdated_plugins = self.get_installed_plugins(true).await?;
+    async fn update_all_plugins(&self, pypi: bool, dry_run: bool) -> Result<()> {
+        let outdated_plugins = self.outdated_plugins(pypi).await?;
 
         if outdated_plugins.is_empty() {
             info!("No plugins need to update.");
             return Ok(());
         }
-        info!("Fount {} outdated plugins:", outdated_plugins.len());
-        outdated_plugins
-            .iter()
-            .for_each(|plugin| plugin.display_info());
 
-        // 确认更新
-        if !Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt(format!(
-                "Would you like to update these {} outdated plugins",
-                outdated_plugins.len()
-            ))
-            .default(true)
-            .interact()?
-        {
+        let registry_plugins = self.fetch_registry_plugins(false).await.ok();
+        let spinner = terminal_utils::create_spinner("Fetching release notes...");
+        let previews = join_all(outdated_plugins.iter().map(|plugin| {
+            let homepage = registry_plugins
+                .and_then(|plugins| plugins.get(&plugin.name))
+                .and_then(|p| p.homepage.as_deref());
+            UpdatePreview::collect(&self.client, &self.work_dir, plugin, homepage)
+        }))
+        .await;
+        spinner.finish_and_clear();
+
+        info!("Found {} outdated plugins:", previews.len());
+        previews.iter().for_each(UpdatePreview::display);
+
+        // 逐个选择需要更新的插件
+        let labels: Vec<String> = previews.iter().map(UpdatePreview::summary).collect();
+        let selected = MultiSelect::new("Select plugins to update", &labels)
+            .defaults(&vec![true; labels.len()])
+            .interact()?;
+        if selected.is_empty() {
             error!("{}", "Update operation cancelled.");
             return Ok(());
         }
 
-        let package_names: Vec<&str> = outdated_plugins.iter().map(|p| p.name.as_str()).collect();
+        let package_names: Vec<&str> = selected
+            .iter()
+            .map(|i| previews[*i].package.name.as_str())
+            .collect();
+        if dry_run {
+            return self.preview_upgrade(&package_names).await;
+        }
         uv::upgrade(package_names.clone())?;
 
         StyledText::new(" ")
@@ -612,6 +1925,19 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Show the `uv.lock` changes an upgrade of `packages` would make
+    async fn preview_upgrade(&self, packages: &[&str]) -> Result<()> {
+        let (before, after) = uv::preview_upgrade(packages, &self.work_dir).await?;
+        let diff = LockDiff::new(&before, &after);
+        if diff.is_empty() {
+            info!("uv.lock would not change.");
+            return Ok(());
+        }
+        info!("uv.lock changes (dry run, nothing updated):");
+        diff.display();
+        Ok(())
+    }
+
     /// Update a single plugin
     fn update_single_plugin(&self, package_name: &str, reinstall: bool) -> Result<()> {
         if reinstall {
@@ -623,11 +1949,6 @@ impl PluginManager {
         Ok(())
     }
 
-    pub fn get_cache_file(&self) -> Result<PathBuf> {
-        let cache_dir = get_cache_dir()?;
-        Ok(cache_dir.join("plugins.json"))
-    }
-
     fn set_registry_plugins(&self, plugins: HashMap<String, RegistryPlugin>) -> Result<()> {
         self.registry_plugins
             .set(plugins)
@@ -648,37 +1969,15 @@ impl PluginManager {
             return Ok(plugins);
         }
 
-        let cache_file = self.get_cache_file()?;
-        if !fetch_remote && cache_file.exists() {
-            debug!("Loading plugins from cache: {}", cache_file.display());
-            let registry_plugins: HashMap<String, RegistryPlugin> =
-                serde_json::from_slice(&std::fs::read(&cache_file)?)?;
-            self.set_registry_plugins(registry_plugins)?;
-            return self.get_registry_plugins();
-        }
-
-        let spinner = terminal_utils::create_spinner("Fetching plugins from registry...");
-        let plugins_json_url = "https://registry.nonebot.dev/plugins.json";
-        let response = self
-            .client
-            .get(plugins_json_url)
-            .send()
-            .await
-            .context("Network error while fetching plugins")?;
-
-        let plugins: Vec<RegistryPlugin> = response
-            .json::<Vec<RegistryPlugin>>()
-            .await
-            .context("Failed to parse plugin info")?;
-
-        spinner.finish_and_clear();
-        let registry_plugins = plugins
-            .iter()
-            .map(|p| (p.project_link.clone(), p.clone()))
-            .collect::<HashMap<String, RegistryPlugin>>();
+        let registry_plugins = registry::fetch_index(
+            &self.client,
+            &self.config.registry,
+            RegistryIndex::Plugins,
+            fetch_remote,
+            |p: &RegistryPlugin| p.project_link.clone(),
+        )
+        .await?;
 
-        // 缓存到文件
-        std::fs::write(cache_file, serde_json::to_string(&registry_plugins)?)?;
         self.set_registry_plugins(registry_plugins)?;
         self.get_registry_plugins()
     }
@@ -696,27 +1995,35 @@ impl PluginManager {
         Ok(plugin)
     }
 
-    /// Search plugins in registry
-    async fn search_registry_plugins(
+    /// Search plugins in registry, ranked by relevance or publish time
+    pub async fn search_registry_plugins(
         &self,
         query: &str,
         limit: usize,
+        filter: &SearchFilter,
         fetch_remote: bool,
     ) -> Result<Vec<&RegistryPlugin>> {
         let plugins_map = self.fetch_registry_plugins(fetch_remote).await?;
+        Ok(Self::rank_plugins(
+            plugins_map.values(),
+            query,
+            limit,
+            filter,
+        ))
+    }
 
-        let results: Vec<&RegistryPlugin> = plugins_map
-            .values()
-            .filter(|plugin| {
-                plugin.project_link.contains(query)
-                    || plugin.name.contains(query)
-                    || plugin.desc.contains(query)
-                    || plugin.author.contains(query)
-            })
-            .take(limit)
-            .collect();
-
-        Ok(results)
+    /// Filter, rank and truncate plugins
+    pub fn rank_plugins<'p>(
+        plugins: impl IntoIterator<Item = &'p RegistryPlugin>,
+        query: &str,
+        limit: usize,
+        filter: &SearchFilter,
+    ) -> Vec<&'p RegistryPlugin> {
+        let mut ranked = search::rank(plugins.into_iter().filter(|p| filter.matches(p)), query);
+        if filter.sort == SearchSort::Time {
+            ranked.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time));
+        }
+        ranked.into_iter().take(limit).map(|(_, p)| p).collect()
     }
 
     /// Display plugin information
@@ -743,13 +2050,7 @@ impl PluginManager {
         }
 
         if !plugin.tags.is_empty() {
-            let tags_str: String = plugin
-                .tags
-                .iter()
-                .filter_map(|t| t.get("label"))
-                .map(|s| s.as_str())
-                .collect::<Vec<_>>()
-                .join(", ");
+            let tags_str = plugin.tag_labels().collect::<Vec<_>>().join(", ");
             StyledText::new(" ")
                 .text("  Tags:")
                 .yellow(tags_str)
@@ -757,6 +2058,56 @@ impl PluginManager {
         }
     }
 
+    /// Display the full registry record of a plugin
+    fn display_plugin_details(&self, plugin: &RegistryPlugin) {
+        self.display_plugin_info(plugin);
+        StyledText::new(" ")
+            .text("  Package:")
+            .white(&plugin.project_link)
+            .println();
+        StyledText::new(" ")
+            .text("  Module:")
+            .white(&plugin.module_name)
+            .println();
+        if let Some(ref plugin_type) = plugin.plugin_type {
+            StyledText::new(" ")
+                .text("  Type:")
+                .white(plugin_type)
+                .println();
+        }
+        StyledText::new(" ")
+            .text("  Official:")
+            .white(if plugin.is_official { "yes" } else { "no" })
+            .println();
+        let adapters = match plugin.supported_adapters {
+            Some(ref adapters) => adapters.join(", "),
+            None => "all".to_string(),
+        };
+        StyledText::new(" ")
+            .text("  Supported Adapters:")
+            .white(adapters)
+            .println();
+        StyledText::new(" ")
+            .text("  Valid:")
+            .with(|text| {
+                if plugin.valid {
+                    text.green("yes");
+                } else {
+                    text.red("no");
+                }
+            })
+            .with(|text| {
+                if plugin.skip_test {
+                    text.yellow("(test skipped)");
+                }
+            })
+            .println();
+        StyledText::new(" ")
+            .text("  Published:")
+            .white(&plugin.time)
+            .println();
+    }
+
     /// Display search result
     fn display_search_result(&self, plugin: &RegistryPlugin, index: usize) {
         StyledText::new("")
@@ -781,6 +2132,613 @@ impl PluginManager {
     }
 }
 
+/// State of a plugin compared between the environment and tool.nonebot.plugins
+#[derive(Debug, Clone, PartialEq)]
+pub enum PluginState {
+    /// Installed and declared in tool.nonebot.plugins
+    Loaded,
+    /// Declared in tool.nonebot.plugins but not installed
+    Missing,
+    /// Installed but not declared in tool.nonebot.plugins
+    Unloaded,
+    /// Installed as a dependency of another plugin, loaded by its `require`
+    Dependency,
+    /// Installed and in tool.nbr.disabled-plugins
+    Disabled,
+}
+
+#[derive(Debug, Clone)]
+pub struct PluginStatus {
+    pub module_name: String,
+    /// Installed package, `None` when missing
+    pub package: Option<Package>,
+    pub state: PluginState,
+}
+
+impl PluginStatus {
+    fn display(&self) {
+        match self.package {
+            Some(ref package) => {
+                package.display_info();
+                if self.state == PluginState::Unloaded {
+                    StyledText::new(" ")
+                        .text("   ")
+                        .yellow(format!(
+                            "⚠ Installed but {} is not in tool.nonebot.plugins",
+                            self.module_name
+                        ))
+                        .println();
+                }
+                if self.state == PluginState::Dependency {
+                    StyledText::new(" ")
+                        .text("   ")
+                        .blue("↳ Required by another plugin")
+                        .println();
+                }
+                if self.state == PluginState::Disabled {
+                    StyledText::new(" ")
+                        .text("   ")
+                        .purple("⏸ Disabled, run `nbr plugin enable` to load it again")
+                        .println();
+                }
+            }
+            None => StyledText::new(" ")
+                .text(" ")
+                .red(&self.module_name)
+                .red("✗ Declared in tool.nonebot.plugins but not installed")
+                .println(),
+        }
+    }
+}
+
+/// Whether the install input is a url, checked after git sources
+fn is_direct_url(input: &str) -> bool {
+    git::SCHEMES.iter().any(|scheme| input.starts_with(scheme))
+}
+
+/// Package name in the file name of a wheel or sdist url, `pkg_name-1.0-py3-none-any.whl`
+fn direct_url_name(url: &str) -> String {
+    let url = url.split(['#', '?']).next().unwrap_or(url);
+    let file = url.trim_end_matches('/').rsplit('/').next().unwrap_or(url);
+    let name = match file.strip_suffix(".whl") {
+        Some(stem) => stem.split('-').next(),
+        None => [".tar.gz", ".tgz", ".tar.bz2", ".zip"]
+            .iter()
+            .find_map(|suffix| file.strip_suffix(suffix))
+            .map(|stem| stem.rsplit_once('-').map_or(stem, |(name, _)| name)),
+    };
+    name.unwrap_or(file).replace('_', "-")
+}
+
+/// Whether the install input is a local directory rather than a package name
+fn is_local_path(input: &str) -> bool {
+    input.starts_with('.') || input.contains(['/', '\\']) || Path::new(input).is_absolute()
+}
+
+/// Compare installed plugin packages with the declared plugin modules
+///
+/// Installed plugins come first in their original order, missing ones last. `required` holds
+/// the normalized names of plugins required by other plugins.
+pub fn reconcile_plugins(
+    declared: &[String],
+    disabled: &[String],
+    required: &HashSet<String>,
+    installed: Vec<Package>,
+    module_of: impl Fn(&str) -> String,
+) -> Vec<PluginStatus> {
+    let mut statuses: Vec<PluginStatus> = installed
+        .into_iter()
+        .map(|package| {
+            let module_name = module_of(&package.name);
+            let state = if declared.contains(&module_name) {
+                PluginState::Loaded
+            } else if disabled.contains(&module_name) {
+                PluginState::Disabled
+            } else if required.contains(&dist::normalize_name(&package.name)) {
+                PluginState::Dependency
+            } else {
+                PluginState::Unloaded
+            };
+            PluginStatus {
+                module_name,
+                package: Some(package),
+                state,
+            }
+        })
+        .collect();
+    let missing: Vec<PluginStatus> = declared
+        .iter()
+        .filter(|module| !statuses.iter().any(|s| &s.module_name == *module))
+        .map(|module| PluginStatus {
+            module_name: module.clone(),
+            package: None,
+            state: PluginState::Missing,
+        })
+        .collect();
+    statuses.extend(missing);
+    statuses
+}
+
+/// Plugin directory of new projects, used when tool.nonebot.plugin_dirs is empty
+const DEFAULT_PLUGIN_DIR: &str = "src/plugins";
+
+/// Prints the fields of a plugin's config model, run with the project's Python
+const PLUGIN_CONFIG_SCRIPT: &str = include_str!("scripts/plugin_config.py");
+
+/// Output of [`PLUGIN_CONFIG_SCRIPT`]
+#[derive(Debug, Clone, Deserialize)]
+struct PluginConfig {
+    /// Whether `PluginMetadata.config` is set
+    config: bool,
+    fields: Vec<ConfigField>,
+}
+
+/// A field of a plugin's pydantic config model
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfigField {
+    pub name: String,
+    /// Alias of the field, or its name
+    pub key: String,
+    #[serde(rename = "type")]
+    pub type_name: String,
+    pub required: bool,
+    /// JSON encoded default value, `None` for required fields
+    pub default: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ConfigField {
+    /// Key in the .env file
+    pub fn env_key(&self) -> String {
+        self.key.to_uppercase()
+    }
+}
+
+/// `.env.{ENVIRONMENT}`, ENVIRONMENT is read from the environment or `.env`, `prod` by default
+pub fn active_env_file(work_dir: &Path) -> PathBuf {
+    let environment = std::env::var("ENVIRONMENT").ok().or_else(|| {
+        fs::read_to_string(work_dir.join(".env"))
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("ENVIRONMENT"))
+            .map(|(_, value)| value.trim().trim_matches(['"', '\'']).to_string())
+    });
+    work_dir.join(format!(".env.{}", environment.as_deref().unwrap_or("prod")))
+}
+
+/// Commented `.env` entries for the fields whose keys are not in `existing`, commented keys included
+pub fn env_entries(module_name: &str, fields: &[ConfigField], existing: &str) -> String {
+    let existing_keys: HashSet<String> = existing
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim().to_uppercase())
+        .collect();
+    let mut entries = String::new();
+    for field in fields {
+        let key = field.env_key();
+        if existing_keys.contains(&key) {
+            continue;
+        }
+        let mut comment = field.type_name.clone();
+        if let Some(ref description) = field.description {
+            comment.push_str(", ");
+            comment.push_str(description);
+        }
+        entries.push_str(&format!(
+            "# {}: {}\n# {}={}\n",
+            field.name,
+            comment,
+            key,
+            field.default.as_deref().unwrap_or_default()
+        ));
+    }
+    if entries.is_empty() {
+        return entries;
+    }
+    format!("\n# {module_name}\n{entries}")
+}
+
+/// A plugin module found in one of tool.nonebot.plugin_dirs
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalPlugin {
+    pub module_name: String,
+    /// The `.py` file or the package directory, relative to the project root
+    pub path: PathBuf,
+    /// `name` of `__plugin_meta__`, if it can be read
+    pub name: Option<String>,
+}
+
+/// Find the modules and packages in `plugin_dirs`, like nonebot's `load_plugins`
+pub fn find_local_plugins(work_dir: &Path, plugin_dirs: &[String]) -> Vec<LocalPlugin> {
+    let mut plugins = Vec::new();
+    for dir in plugin_dirs {
+        let Ok(entries) = fs::read_dir(work_dir.join(dir)) else {
+            continue;
+        };
+        let mut dir_plugins: Vec<LocalPlugin> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                // nonebot 跳过以 _ 开头的模块
+                if file_name.starts_with(['_', '.']) {
+                    return None;
+                }
+                let (module_name, source) = if path.join("__init__.py").is_file() {
+                    (file_name.clone(), path.join("__init__.py"))
+                } else {
+                    (file_name.strip_suffix(".py")?.to_string(), path.clone())
+                };
+                Some(LocalPlugin {
+                    module_name,
+                    path: Path::new(dir).join(&file_name),
+                    name: plugin_meta_name(&fs::read_to_string(source).unwrap_or_default()),
+                })
+            })
+            .collect();
+        dir_plugins.sort_by(|a, b| a.module_name.cmp(&b.module_name));
+        plugins.extend(dir_plugins);
+    }
+    plugins
+}
+
+/// Read `name` from `__plugin_meta__ = PluginMetadata(...)` in a plugin source
+fn plugin_meta_name(source: &str) -> Option<String> {
+    let meta = &source[source.find("__plugin_meta__")?..];
+    let meta = &meta[meta.find("PluginMetadata(")?..];
+    let re = Regex::new(r#"\bname\s*=\s*["']([^"']*)["']"#).ok()?;
+    re.captures(meta)
+        .and_then(|captures| captures.get(1))
+        .map(|name| name.as_str().to_string())
+}
+
+fn normalize_plugin_dir(dir: &str) -> &str {
+    let dir = dir.trim_end_matches(['/', '\\']);
+    dir.strip_prefix("./").unwrap_or(dir)
+}
+
+/// Options for scaffolding a local plugin
+#[derive(Debug, Clone)]
+pub struct PluginOptions {
+    /// Display name written to `PluginMetadata`
+    pub name: String,
+    /// Importable module name
+    pub module_name: String,
+    pub layout: PluginLayout,
+    /// Plugin directory, relative to the project root
+    pub plugin_dir: String,
+    /// Generate a nonebug test under `tests/plugins`
+    pub with_test: bool,
+}
+
+fn gather_plugin_options(
+    work_dir: &Path,
+    name: Option<String>,
+    module_name: Option<String>,
+    layout: Option<PluginLayout>,
+    dir: Option<String>,
+    with_test: Option<bool>,
+) -> Result<PluginOptions> {
+    let name = match name {
+        Some(name) => name,
+        None => Input::new("Plugin name")
+            .interact_text()
+            .context("Failed to get plugin name")?,
+    };
+    let module_name = match module_name {
+        Some(module_name) => {
+            validate_module_name(&module_name)?;
+            module_name
+        }
+        None => {
+            // 非 ASCII 的插件名无法推断模块名，需手动输入
+            let mut input = Input::new("Module name").validate_with(validate_module_name);
+            if let Some(default) = plugin_module_name(&name) {
+                input = input.default(default);
+            }
+            input
+                .interact_text()
+                .context("Failed to get module name, pass it with --module-name")?
+        }
+    };
+
+    let layout = match layout {
+        Some(layout) => layout,
+        None => {
+            let layouts = PluginLayout::value_variants();
+            let selected = Select::new("Which layout would you like to use", layouts)
+                .default(1)
+                .interact()?;
+            layouts[selected].clone()
+        }
+    };
+
+    let plugin_dirs = PyProjectConfig::parse(Some(work_dir))?
+        .nonebot()
+        .and_then(|nonebot| nonebot.plugin_dirs.clone())
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| vec![DEFAULT_PLUGIN_DIR.to_string()]);
+    let plugin_dir = match dir {
+        Some(dir) => {
+            if !plugin_dirs.contains(&dir) {
+                warn!("'{}' is not listed in tool.nonebot.plugin_dirs", dir);
+            }
+            dir
+        }
+        None if plugin_dirs.len() == 1 => plugin_dirs[0].clone(),
+        None => {
+            let selected =
+                Select::new("Which plugin directory would you like to use", &plugin_dirs)
+                    .default(0)
+                    .interact()?;
+            plugin_dirs[selected].clone()
+        }
+    };
+
+    let with_test = match with_test {
+        Some(with_test) => with_test,
+        None => Confirm::new("Would you like to generate a nonebug test?")
+            .default(true)
+            .interact()?,
+    };
+
+    Ok(PluginOptions {
+        name,
+        module_name,
+        layout,
+        plugin_dir,
+        with_test,
+    })
+}
+
+/// Quote a string as a Python string literal, `say "hi"` -> `"say \"hi\""`
+fn python_string(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Derive a module name from a plugin name, `Hello World` -> `hello_world`
+///
+/// `None` if the name does not map to an identifier, e.g. `天气`.
+fn plugin_module_name(name: &str) -> Option<String> {
+    let module_name = name.trim().replace(['-', ' '], "_").to_lowercase();
+    validate_module_name(&module_name).ok()?;
+    Some(module_name)
+}
+
+fn validate_module_name(module_name: &str) -> Result<()> {
+    let re = Regex::new(r"^[a-z_][a-z0-9_]*$").context("Invalid regex pattern")?;
+    if !re.is_match(module_name) {
+        anyhow::bail!(
+            "Invalid module name '{}', use lowercase letters, digits and underscores",
+            module_name
+        );
+    }
+    Ok(())
+}
+
+/// Scaffold a plugin into one of the project's plugin directories
+pub fn create_plugin(work_dir: &Path, options: &PluginOptions) -> Result<()> {
+    let plugin_dir = work_dir.join(&options.plugin_dir);
+    fs::create_dir_all(&plugin_dir)
+        .with_context(|| format!("Failed to create directory: {}", plugin_dir.display()))?;
+
+    let plugin_path = match options.layout {
+        PluginLayout::File => {
+            let plugin_file = plugin_dir.join(format!("{}.py", options.module_name));
+            if plugin_file.exists() {
+                anyhow::bail!("Plugin '{}' already exists", plugin_file.display());
+            }
+            let content = format!(
+                include_str!("templates/plugin/single.py"),
+                python_string(&options.name)
+            );
+            fs::write(&plugin_file, content)?;
+            format!("{}/{}.py", options.plugin_dir, options.module_name)
+        }
+        PluginLayout::Package => {
+            let package_dir = plugin_dir.join(&options.module_name);
+            if package_dir.exists() {
+                anyhow::bail!("Plugin '{}' already exists", package_dir.display());
+            }
+            fs::create_dir_all(&package_dir)?;
+            let content = format!(
+                include_str!("templates/plugin/init.py"),
+                python_string(&options.name)
+            );
+            fs::write(package_dir.join("__init__.py"), content)?;
+            fs::write(
+                package_dir.join("config.py"),
+                include_str!("templates/plugin/config.py"),
+            )?;
+            format!("{}/{}", options.plugin_dir, options.module_name)
+        }
+    };
+
+    if options.with_test {
+        let tests_dir = work_dir.join("tests").join("plugins");
+        fs::create_dir_all(&tests_dir)?;
+        let test_file = tests_dir.join(format!("test_{}.py", options.module_name));
+        if test_file.exists() {
+            warn!(
+                "Test file '{}' already exists, skipped",
+                test_file.display()
+            );
+        } else {
+            let content = format!(
+                include_str!("templates/plugin/test.py"),
+                options.module_name, plugin_path
+            );
+            fs::write(&test_file, content)?;
+        }
+    }
+
+    StyledText::new(" ")
+        .green_bold("✓ Successfully created plugin:")
+        .cyan_bold(&plugin_path)
+        .println();
+    Ok(())
+}
+
+/// Check a plugin project against the registry publishing rules
+pub async fn check_plugin(dir: &Path, skip_import: bool) -> Result<()> {
+    let mut items = Vec::new();
+    match PluginProject::load(dir) {
+        Ok(project) => {
+            StyledText::new(" ")
+                .text("Checking")
+                .cyan_bold(&project.project_link)
+                .println();
+            items.extend(project.check_static());
+            if skip_import {
+                items.push(CheckItem::new(
+                    "import",
+                    CheckStatus::Skip,
+                    "Skipped by --skip-import",
+                ));
+            } else {
+                let spinner = terminal_utils::create_spinner(
+                    "Installing the plugin into an isolated venv...",
+                );
+                items.push(project.check_import().await);
+                spinner.finish_and_clear();
+            }
+        }
+        Err(e) => items.push(CheckItem::new(
+            "pyproject",
+            CheckStatus::Fail,
+            format!("{:#}", e),
+        )),
+    }
+
+    for item in &items {
+        item.display();
+    }
+    let failed = items
+        .iter()
+        .filter(|item| item.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    StyledText::new(" ")
+        .green_bold("✓ All checks passed")
+        .println();
+    Ok(())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PublishOptions<'a> {
+    pub upload: bool,
+    pub publish_url: Option<&'a str>,
+    /// Upload credentials, `uv publish` reads its own environment and keyring when unset
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    pub tags: Vec<Tag>,
+    pub skip_check: bool,
+    pub skip_import: bool,
+}
+
+/// Build a plugin, optionally upload it, and write its registry submission to `dist/`
+pub async fn publish_plugin(dir: &Path, options: PublishOptions<'_>) -> Result<()> {
+    // 先检查再读取，pyproject 的问题以检查项报告
+    if !options.skip_check {
+        check_plugin(dir, options.skip_import).await?;
+    }
+    let project = PluginProject::load(dir)?;
+    let submission = RegistrySubmission::from_project(&project, options.tags)?;
+
+    let started = SystemTime::now();
+    uv::CmdBuilder::uv(vec!["build", "--out-dir", "dist"])
+        .working_dir(dir)
+        .timeout(600)
+        .run_async_with_spinner("Building the plugin...")
+        .await?;
+    let dist_dir = dir.join("dist");
+    let files = publish::built_files(&dist_dir, started)?;
+    let file_names: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .collect();
+    StyledText::new(" ")
+        .green_bold("✓ Successfully built:")
+        .cyan(file_names.join(", "))
+        .println();
+
+    if options.upload {
+        let config = NbrConfig::load_with_project(dir)?;
+        let publish_url = options
+            .publish_url
+            .map(str::to_string)
+            .or(config.publish.publish_url);
+        let mut args = vec!["publish"];
+        if let Some(ref url) = publish_url {
+            args.extend(["--publish-url", url.as_str()]);
+        }
+        let files: Vec<String> = files
+            .iter()
+            .map(|file| file.to_string_lossy().to_string())
+            .collect();
+        args.extend(files.iter().map(String::as_str));
+        let mut publish = uv::CmdBuilder::uv(args);
+        publish.working_dir(dir).timeout(600);
+        if let Some(username) = options.username {
+            publish.env("UV_PUBLISH_USERNAME", username);
+        }
+        if let Some(password) = options.password {
+            publish.env("UV_PUBLISH_PASSWORD", password);
+        }
+        publish
+            .run_async_with_spinner("Uploading the plugin...")
+            .await?;
+        StyledText::new(" ")
+            .green_bold("✓ Successfully uploaded to")
+            .cyan(publish_url.as_deref().unwrap_or("PyPI"))
+            .println();
+    }
+
+    let payload_file = dist_dir.join("registry.json");
+    let issue_file = dist_dir.join("registry-issue.md");
+    let issue_body = submission.issue_body()?;
+    fs::write(&payload_file, serde_json::to_string_pretty(&submission)?)?;
+    fs::write(&issue_file, &issue_body)?;
+
+    println!();
+    StyledText::new(" ")
+        .text("Issue title:")
+        .cyan_bold(submission.issue_title())
+        .println();
+    println!("{}", issue_body.trim_end());
+    println!();
+    StyledText::new(" ")
+        .green_bold("✓ Registry submission written to")
+        .cyan(payload_file.display().to_string())
+        .text("and")
+        .cyan(issue_file.display().to_string())
+        .println();
+    StyledText::new(" ")
+        .text("Open an issue at")
+        .cyan(publish::PUBLISH_ISSUE_URL)
+        .println();
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -872,6 +2830,20 @@ mod tests {
                 extras: None,
                 specifier: None,
             },
+            TestCase {
+                input: "git+ssh://git@gitlab.com/owner/weather-bot.git@v1.2.0#subdirectory=plugin",
+                name: "weather-bot",
+                module_name: "weather_bot",
+                extras: None,
+                specifier: None,
+            },
+            TestCase {
+                input: "git+https://gitea.example.com/owner/nonebot-plugin-test@1a2b3c4",
+                name: "nonebot-plugin-test",
+                module_name: "nonebot_plugin_test",
+                extras: None,
+                specifier: None,
+            },
         ];
         for test_case in test_cases {
             let options = InstallOptions::new(test_case.input, false, false, None)
@@ -880,7 +2852,287 @@ mod tests {
             assert_eq!(options.module_name, test_case.module_name);
             assert_eq!(options.extras, test_case.extras);
             assert_eq!(options.specifier, test_case.specifier);
-            assert_eq!(options.git_url, Some(test_case.input));
+            assert_eq!(
+                options.git.map(|git| git.requirement()).as_deref(),
+                Some(test_case.input)
+            );
+        }
+    }
+
+    #[test]
+    fn test_plugin_module_name() {
+        assert_eq!(
+            plugin_module_name("Hello World").as_deref(),
+            Some("hello_world")
+        );
+        assert_eq!(
+            plugin_module_name("weather-forecast").as_deref(),
+            Some("weather_forecast")
+        );
+        assert!(plugin_module_name("天气").is_none());
+        assert!(validate_module_name("weather").is_ok());
+        assert!(validate_module_name("1weather").is_err());
+    }
+
+    #[test]
+    fn test_python_string() {
+        assert_eq!(python_string("天气"), r#""天气""#);
+        assert_eq!(python_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(python_string(r"C:\bot"), r#""C:\\bot""#);
+        assert_eq!(python_string("a\nb"), r#""a\nb""#);
+    }
+
+    #[test]
+    fn test_reconcile_plugins() {
+        let package = |name: &str| Package {
+            name: name.to_string(),
+            version: "0.1.0".to_string(),
+            latest_version: None,
+            location: None,
+            requires: None,
+            requires_by: None,
+        };
+        let declared = vec![
+            "nonebot_plugin_status".to_string(),
+            "nonebot_plugin_missing".to_string(),
+        ];
+        let disabled = vec!["nonebot_plugin_disabled".to_string()];
+        let installed = vec![
+            package("nonebot-plugin-status"),
+            package("nonebot-plugin-unloaded"),
+            package("nonebot-plugin-disabled"),
+            package("nonebot-plugin-localstore"),
+        ];
+        let required = HashSet::from(["nonebot_plugin_localstore".to_string()]);
+        let statuses = reconcile_plugins(&declared, &disabled, &required, installed, |name| {
+            name.replace("-", "_")
+        });
+        let states: Vec<(&str, &PluginState)> = statuses
+            .iter()
+            .map(|s| (s.module_name.as_str(), &s.state))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ("nonebot_plugin_status", &PluginState::Loaded),
+                ("nonebot_plugin_unloaded", &PluginState::Unloaded),
+                ("nonebot_plugin_disabled", &PluginState::Disabled),
+                ("nonebot_plugin_localstore", &PluginState::Dependency),
+                ("nonebot_plugin_missing", &PluginState::Missing),
+            ]
+        );
+        assert!(statuses[4].package.is_none());
+    }
+
+    #[test]
+    fn test_install_requirement() {
+        let requirement = |input| {
+            InstallOptions::new(input, false, false, None)
+                .unwrap()
+                .requirement()
+        };
+        assert_eq!(requirement("nonebot-plugin-test"), "nonebot-plugin-test");
+        assert_eq!(
+            requirement("nonebot-plugin-test[extra1,extra2]"),
+            "nonebot-plugin-test[extra1,extra2]"
+        );
+        assert_eq!(
+            requirement("nonebot-plugin-test[extra1, extra2] >= 1.2, < 2"),
+            "nonebot-plugin-test[extra1,extra2]>=1.2,<2"
+        );
+        assert_eq!(
+            requirement("nonebot-plugin-test~=1.2.0"),
+            "nonebot-plugin-test~=1.2.0"
+        );
+        assert_eq!(
+            requirement("git+https://github.com/owner/nonebot-plugin-test"),
+            "git+https://github.com/owner/nonebot-plugin-test"
+        );
+        assert!(InstallOptions::new("nonebot-plugin-test>=", false, false, None).is_err());
+    }
+
+    #[test]
+    fn test_local_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let package_dir = temp_dir.path().join("nonebot-plugin-foo");
+        fs::create_dir_all(package_dir.join("src/foo")).unwrap();
+        fs::write(
+            package_dir.join("pyproject.toml"),
+            "[project]\nname = \"nonebot-plugin-foo\"\n",
+        )
+        .unwrap();
+        fs::write(package_dir.join("src/foo/__init__.py"), "").unwrap();
+
+        let input = package_dir.to_str().unwrap();
+        let options = InstallOptions::new(input, false, false, None).unwrap();
+        assert_eq!(options.name, "nonebot-plugin-foo");
+        assert_eq!(options.module_name, "foo");
+        assert_eq!(options.path, Some(input));
+        assert_eq!(options.requirement(), input);
+
+        assert!(is_local_path("../nonebot-plugin-foo"));
+        assert!(!is_local_path("nonebot-plugin-foo"));
+        assert!(InstallOptions::new("./missing", false, false, None).is_err());
+    }
+
+    #[test]
+    fn test_install_direct_url() {
+        let url = "https://files.example.com/nonebot_plugin_weather-0.1.0-py3-none-any.whl";
+        let options = InstallOptions::new(url, false, false, None).unwrap();
+        assert!(options.git.is_none());
+        assert_eq!(options.url, Some(url));
+        assert_eq!(options.name, "nonebot-plugin-weather");
+        assert_eq!(options.requirement(), url);
+
+        assert_eq!(
+            direct_url_name("https://example.com/dist/nonebot-plugin-weather-1.0.tar.gz#sha256=0"),
+            "nonebot-plugin-weather"
+        );
+        let options =
+            InstallOptions::new("https://github.com/owner/weather", false, false, None).unwrap();
+        assert!(options.git.is_some());
+    }
+
+    #[test]
+    fn test_env_entries() {
+        let field = |name: &str, default: Option<&str>, description: Option<&str>| ConfigField {
+            name: name.to_string(),
+            key: name.to_string(),
+            type_name: "str".to_string(),
+            required: default.is_none(),
+            default: default.map(String::from),
+            description: description.map(String::from),
+        };
+        let fields = [
+            field(
+                "status_template",
+                Some("\"{cpu}\""),
+                Some("Status template"),
+            ),
+            field("status_token", None, None),
+            field("status_enabled", Some("true"), None),
+        ];
+        let existing = "ENVIRONMENT=dev\nstatus_enabled=false\n# STATUS_TOKEN=secret\n";
+        assert_eq!(
+            env_entries("nonebot_plugin_status", &fields, existing),
+            "\n# nonebot_plugin_status\n\
+            # status_template: str, Status template\n\
+            # STATUS_TEMPLATE=\"{cpu}\"\n"
+        );
+        assert!(env_entries("nonebot_plugin_status", &fields[1..], existing).is_empty());
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join(".env"), "ENVIRONMENT=dev\n").unwrap();
+        if std::env::var("ENVIRONMENT").is_err() {
+            assert_eq!(
+                active_env_file(temp_dir.path()),
+                temp_dir.path().join(".env.dev")
+            );
+        }
+    }
+
+    fn registry_plugin(
+        project_link: &str,
+        name: &str,
+        desc: &str,
+        time: &str,
+        supported_adapters: Option<Vec<&str>>,
+    ) -> RegistryPlugin {
+        RegistryPlugin {
+            module_name: project_link.replace("-", "_"),
+            project_link: project_link.to_string(),
+            name: name.to_string(),
+            desc: desc.to_string(),
+            author: "nonebot".to_string(),
+            homepage: None,
+            tags: vec![HashMap::from([("label".to_string(), "server".to_string())])],
+            is_official: project_link.ends_with("status"),
+            plugin_type: Some("application".to_string()),
+            supported_adapters: supported_adapters
+                .map(|adapters| adapters.into_iter().map(String::from).collect()),
+            valid: true,
+            time: time.to_string(),
+            version: "0.1.0".to_string(),
+            skip_test: false,
         }
     }
+
+    #[test]
+    fn test_rank_plugins_with_filter() {
+        let plugins = [
+            registry_plugin(
+                "nonebot-plugin-status",
+                "服务器状态查看",
+                "通过戳一戳获取服务器状态",
+                "2024-09-03T09:20:59.379554Z",
+                None,
+            ),
+            registry_plugin(
+                "nonebot-plugin-server-monitor",
+                "Server Monitor",
+                "Monitor server status",
+                "2025-01-01T00:00:00Z",
+                Some(vec!["~onebot.v11"]),
+            ),
+        ];
+
+        let filter = SearchFilter::default();
+        let results = PluginManager::rank_plugins(&plugins, "STATUS", 10, &filter);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].project_link, "nonebot-plugin-status");
+
+        let filter = SearchFilter {
+            sort: SearchSort::Time,
+            ..Default::default()
+        };
+        let results = PluginManager::rank_plugins(&plugins, "status", 1, &filter);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].project_link, "nonebot-plugin-server-monitor");
+
+        let filter = SearchFilter {
+            official: true,
+            ..Default::default()
+        };
+        let results = PluginManager::rank_plugins(&plugins, "server", 10, &filter);
+        assert_eq!(results.len(), 1);
+
+        let filter = SearchFilter {
+            adapter: Some("telegram".to_string()),
+            tag: Some("Server".to_string()),
+            plugin_type: Some("application".to_string()),
+            ..Default::default()
+        };
+        let results = PluginManager::rank_plugins(&plugins, "server", 10, &filter);
+        // supported_adapters 为 null 表示支持所有适配器
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].project_link, "nonebot-plugin-status");
+
+        assert!(plugins[1].supports_adapter("nonebot.adapters.onebot.v11"));
+        assert!(plugins[1].supports_adapter("OneBot.V11"));
+    }
+
+    #[test]
+    fn test_adapter_compatibility() {
+        let plugin = registry_plugin(
+            "nonebot-plugin-server-monitor",
+            "Server Monitor",
+            "Monitor server status",
+            "2025-01-01T00:00:00Z",
+            Some(vec!["~onebot.v11", "~telegram"]),
+        );
+        let adapters = |modules: &[&str]| modules.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+
+        assert!(plugin.is_compatible_with(&[]));
+        assert!(plugin.is_compatible_with(&adapters(&[
+            "nonebot.adapters.console",
+            "nonebot.adapters.telegram"
+        ])));
+        assert!(!plugin.is_compatible_with(&adapters(&[
+            "nonebot.adapters.console",
+            "nonebot.adapters.onebot.v12"
+        ])));
+
+        let plugin = registry_plugin("nonebot-plugin-status", "", "", "", None);
+        assert!(plugin.is_compatible_with(&adapters(&["nonebot.adapters.console"])));
+    }
 }
@@ -0,0 +1,63 @@
+# Print the fields of a plugin's config model as JSON
+# usage: python -c <this script> <module> [plugin_dir ...]
+import json
+import sys
+
+sys.path[:0] = sys.argv[2:]
+
+import nonebot
+
+try:
+    nonebot.init()
+except Exception:
+    nonebot.init(driver="~none")
+
+plugin = nonebot.load_plugin(sys.argv[1])
+if plugin is None:
+    sys.exit(f"Failed to load plugin {sys.argv[1]}")
+config = plugin.metadata.config if plugin.metadata else None
+
+
+def type_name(annotation):
+    if isinstance(annotation, type):
+        return annotation.__name__
+    return repr(annotation).replace("typing.", "")
+
+
+def dump(value):
+    try:
+        return json.dumps(value, ensure_ascii=False)
+    except TypeError:
+        return json.dumps(str(value), ensure_ascii=False)
+
+
+fields = []
+if config is not None and hasattr(config, "model_fields"):
+    # pydantic v2
+    for name, field in config.model_fields.items():
+        required = field.is_required()
+        fields.append(
+            {
+                "name": name,
+                "key": field.alias or name,
+                "type": type_name(field.annotation),
+                "required": required,
+                "default": None if required else dump(field.get_default(call_default_factory=True)),
+                "description": field.description,
+            }
+        )
+elif config is not None:
+    # pydantic v1
+    for name, field in config.__fields__.items():
+        fields.append(
+            {
+                "name": name,
+                "key": field.alias or name,
+                "type": type_name(field.outer_type_),
+                "required": field.required,
+                "default": None if field.required else dump(field.get_default()),
+                "description": field.field_info.description,
+            }
+        )
+
+print("__NBR_CONFIG__" + json.dumps({"config": config is not None, "fields": fields}))
@@ -0,0 +1,16 @@
+from nonebot import get_plugin_config
+from nonebot.plugin import PluginMetadata
+
+from .config import Config
+
+__plugin_meta__ = PluginMetadata(
+    name={},
+    description="",
+    usage="",
+    type="application",
+    homepage=None,
+    config=Config,
+    supported_adapters=None,
+)
+
+config = get_plugin_config(Config)
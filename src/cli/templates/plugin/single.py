@@ -0,0 +1,20 @@
+from nonebot import get_plugin_config
+from nonebot.plugin import PluginMetadata
+from pydantic import BaseModel
+
+
+class Config(BaseModel):
+    """Plugin Config Here"""
+
+
+__plugin_meta__ = PluginMetadata(
+    name={},
+    description="",
+    usage="",
+    type="application",
+    homepage=None,
+    config=Config,
+    supported_adapters=None,
+)
+
+config = get_plugin_config(Config)
@@ -0,0 +1,12 @@
+from pathlib import Path
+
+import pytest
+from nonebug import App
+
+
+@pytest.mark.asyncio
+async def test_load_{}(app: App):
+    import nonebot
+
+    plugin = nonebot.load_plugin(Path("{}"))
+    assert plugin is not None
@@ -2,14 +2,163 @@
 //!
 //! This module handles loading, saving, and managing configuration files
 //! for both global user settings and project-specific configurations.
-use anyhow::Result;
+use anyhow::{Context, Result};
 use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tracing::debug;
+use tracing::{debug, warn};
+
+/// Global nbr configuration, loaded from `config.toml` in the config directory
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NbrConfig {
+    pub registry: RegistryConfig,
+    pub publish: PublishConfig,
+}
+
+/// Registry configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RegistryConfig {
+    /// Seconds before cached registry data is revalidated
+    pub cache_ttl: u64,
+    /// Whether registry.nonebot.dev is used after the configured sources
+    pub use_official: bool,
+    /// Extra registry sources, earlier sources take priority
+    pub sources: Vec<RegistrySource>,
+}
+
+impl Default for RegistryConfig {
+    fn default() -> Self {
+        Self {
+            cache_ttl: 24 * 60 * 60,
+            use_official: true,
+            sources: vec![],
+        }
+    }
+}
+
+/// A registry source, a mirror or a private registry
+///
+/// Urls can be `http(s)://` or `file://` paths.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegistrySource {
+    pub name: String,
+    /// Url of the plugins index (plugins.json)
+    pub plugins: Option<String>,
+    /// Url of the adapters index (adapters.json)
+    pub adapters: Option<String>,
+}
+
+/// Plugin publishing configuration, used by `nbr plugin publish`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PublishConfig {
+    /// Upload url passed to `uv publish`, PyPI if not set
+    pub publish_url: Option<String>,
+}
+
+/// Project-level nbr configuration, `[tool.nbr]` in pyproject.toml
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ProjectConfig {
+    pub registry: ProjectRegistryConfig,
+    pub publish: PublishConfig,
+    /// Plugin modules turned off with `nbr plugin disable`, still installed
+    pub disabled_plugins: Vec<String>,
+}
+
+/// Project-level registry configuration, overrides the global one
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ProjectRegistryConfig {
+    pub cache_ttl: Option<u64>,
+    pub use_official: Option<bool>,
+    /// Project sources take priority over global sources
+    pub sources: Vec<RegistrySource>,
+}
+
+impl ProjectConfig {
+    /// Parse `[tool.nbr]` from pyproject.toml, `None` if there is no pyproject.toml
+    pub fn parse(work_dir: &Path) -> Result<Option<Self>> {
+        let toml_path = work_dir.join("pyproject.toml");
+        if !toml_path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&toml_path).context("Failed to read pyproject.toml")?;
+        Self::parse_from_str(&content).map(Some)
+    }
+
+    pub fn parse_from_str(content: &str) -> Result<Self> {
+        let value: toml::Table =
+            toml::from_str(content).context("Failed to parse pyproject.toml")?;
+        let Some(nbr) = value.get("tool").and_then(|tool| tool.get("nbr")) else {
+            return Ok(Self::default());
+        };
+        nbr.clone()
+            .try_into()
+            .context("Failed to parse tool.nbr in pyproject.toml")
+    }
+}
+
+impl NbrConfig {
+    /// Load the global config, fall back to defaults if the file does not exist
+    pub fn load() -> Result<Self> {
+        let config_file = get_config_dir()?.join("config.toml");
+        Self::load_from(&config_file)
+    }
+
+    pub fn load_from(config_file: &Path) -> Result<Self> {
+        if !config_file.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(config_file)
+            .with_context(|| format!("Failed to read {}", config_file.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", config_file.display()))
+    }
+
+    /// Load the global config merged with the project config in `work_dir`
+    pub fn load_with_project(work_dir: &Path) -> Result<Self> {
+        let mut config = Self::load()?;
+        if let Some(project) = ProjectConfig::parse(work_dir)? {
+            config.merge_project(project);
+        }
+        Ok(config)
+    }
+
+    /// Like [`NbrConfig::load_with_project`], falling back to the defaults with a warning
+    ///
+    /// For commands that only need the registry settings, so a broken config does not
+    /// break commands that never use it.
+    pub fn load_with_project_or_default(work_dir: &Path) -> Self {
+        Self::load_with_project(work_dir).unwrap_or_else(|e| {
+            warn!("Using the default nbr config: {:#}", e);
+            Self::default()
+        })
+    }
+
+    /// Apply project overrides, project sources are placed before global sources
+    pub fn merge_project(&mut self, project: ProjectConfig) {
+        let registry = project.registry;
+        if let Some(cache_ttl) = registry.cache_ttl {
+            self.registry.cache_ttl = cache_ttl;
+        }
+        if let Some(use_official) = registry.use_official {
+            self.registry.use_official = use_official;
+        }
+        let global_sources = std::mem::take(&mut self.registry.sources);
+        self.registry.sources = registry.sources;
+        self.registry.sources.extend(global_sources);
+
+        if project.publish.publish_url.is_some() {
+            self.publish.publish_url = project.publish.publish_url;
+        }
+    }
+}
 
 /// Get platform-specific configuration directory
-#[allow(unused)]
 pub(crate) fn get_config_dir() -> Result<PathBuf> {
     let config_dir = if let Some(proj_dirs) = ProjectDirs::from("dev", "nonebot", "nbr") {
         proj_dirs.config_dir().to_path_buf()
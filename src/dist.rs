@@ -0,0 +1,472 @@
+//! Installed distribution metadata module for nbr
+//!
+//! Reads the `*.dist-info` directories in the project virtual environment to tell
+//! which installed packages are NoneBot plugins and which modules they provide.
+use reqwest::Url;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Normalize a distribution name for comparison, `Nonebot-Plugin.Status` -> `nonebot_plugin_status`
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['-', '.'], "_")
+}
+
+/// Package name of a PEP 508 requirement, `nonebot2[fastapi]>=2.4` -> `nonebot2`
+pub fn requirement_name(requirement: &str) -> &str {
+    let end = requirement
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(requirement.len());
+    &requirement[..end]
+}
+
+/// Importable module of an installed package, read from its installed metadata
+///
+/// Falls back to the package name with `-` replaced by `_` when it is not installed.
+pub fn module_name(work_dir: &Path, package_name: &str) -> String {
+    Distribution::find(work_dir, package_name)
+        .and_then(|distribution| distribution.import_module(package_name))
+        .unwrap_or_else(|| package_name.replace("-", "_"))
+}
+
+/// Site-packages directories of the `.venv` in `work_dir`
+pub fn site_packages_dirs(work_dir: &Path) -> Vec<PathBuf> {
+    let venv = work_dir.join(".venv");
+    // Windows
+    let mut dirs = vec![venv.join("Lib").join("site-packages")];
+    // Unix, .venv/lib/python3.x/site-packages
+    if let Ok(entries) = fs::read_dir(venv.join("lib")) {
+        dirs.extend(
+            entries
+                .flatten()
+                .filter(|e| e.file_name().to_string_lossy().starts_with("python"))
+                .map(|e| e.path().join("site-packages")),
+        );
+    }
+    dirs.retain(|dir| dir.is_dir());
+    dirs
+}
+
+/// Importable module of a package source directory, `src/` layouts included
+///
+/// Prefers the module named like the package, then the only candidate.
+pub fn source_module(package_dir: &Path, package_name: &str) -> Option<String> {
+    const IGNORED: [&str; 6] = ["tests", "test", "docs", "examples", "scripts", "setup"];
+    let package_name = normalize_name(package_name);
+    let mut modules: Vec<String> = Vec::new();
+    for root in [package_dir.join("src"), package_dir.to_path_buf()] {
+        let Ok(entries) = fs::read_dir(&root) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let module = if path.join("__init__.py").is_file() {
+                path.file_name().and_then(|name| name.to_str())
+            } else if path.extension().is_some_and(|ext| ext == "py") {
+                path.file_stem().and_then(|name| name.to_str())
+            } else {
+                None
+            };
+            if let Some(module) = module
+                && !module.starts_with(['.', '_'])
+                && !IGNORED.contains(&module)
+            {
+                modules.push(module.to_string());
+            }
+        }
+    }
+    modules.sort();
+    match modules
+        .iter()
+        .find(|module| normalize_name(module) == package_name)
+    {
+        Some(module) => Some(module.clone()),
+        None if modules.len() == 1 => modules.pop(),
+        None => None,
+    }
+}
+
+/// `direct_url.json` of a distribution installed from a url or a local path (PEP 610)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DirectUrl {
+    pub url: String,
+    #[serde(default)]
+    dir_info: Option<DirInfo>,
+    #[serde(default)]
+    vcs_info: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct DirInfo {
+    #[serde(default)]
+    editable: bool,
+}
+
+impl DirectUrl {
+    /// The local directory the distribution was installed from
+    ///
+    /// Decodes percent-encoded characters and Windows drive paths (`file:///C:/...`).
+    pub fn local_path(&self) -> Option<PathBuf> {
+        if self.vcs_info.is_some() || self.dir_info.is_none() {
+            return None;
+        }
+        Url::parse(&self.url).ok()?.to_file_path().ok()
+    }
+
+    pub fn is_editable(&self) -> bool {
+        self.dir_info.as_ref().is_some_and(|info| info.editable)
+    }
+}
+
+/// An installed distribution
+#[derive(Debug, Clone)]
+pub struct Distribution {
+    /// The site-packages directory containing the distribution
+    site_packages: PathBuf,
+    /// The `*.dist-info` directory
+    dist_info: PathBuf,
+}
+
+impl Distribution {
+    /// Find an installed distribution by name in the `.venv` of `work_dir`
+    pub fn find(work_dir: &Path, package_name: &str) -> Option<Self> {
+        site_packages_dirs(work_dir)
+            .into_iter()
+            .find_map(|dir| Self::find_in(&dir, package_name))
+    }
+
+    /// Find an installed distribution by name in a site-packages directory
+    pub fn find_in(site_packages: &Path, package_name: &str) -> Option<Self> {
+        let package_name = normalize_name(package_name);
+        fs::read_dir(site_packages)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path())
+            .find(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_suffix(".dist-info"))
+                    .and_then(|name| name.rsplit_once('-'))
+                    .is_some_and(|(name, _version)| normalize_name(name) == package_name)
+            })
+            .map(|dist_info| Self {
+                site_packages: site_packages.to_path_buf(),
+                dist_info,
+            })
+    }
+
+    fn read(&self, file: &str) -> Option<String> {
+        fs::read_to_string(self.dist_info.join(file)).ok()
+    }
+
+    /// Where the distribution was installed from, `None` for index installs
+    pub fn direct_url(&self) -> Option<DirectUrl> {
+        serde_json::from_str(&self.read("direct_url.json")?).ok()
+    }
+
+    /// Trove classifiers from METADATA
+    pub fn classifiers(&self) -> Vec<String> {
+        self.read("METADATA")
+            .unwrap_or_default()
+            .lines()
+            // 空行之后是 long description
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.strip_prefix("Classifier:"))
+            .map(|classifier| classifier.trim().to_string())
+            .collect()
+    }
+
+    /// `Project-URL` entries and `Home-page` from METADATA, as (label, url)
+    pub fn project_urls(&self) -> Vec<(String, String)> {
+        self.read("METADATA")
+            .unwrap_or_default()
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| {
+                if let Some(url) = line.strip_prefix("Home-page:") {
+                    return Some(("Homepage".to_string(), url.trim().to_string()));
+                }
+                let (label, url) = line.strip_prefix("Project-URL:")?.split_once(',')?;
+                Some((label.trim().to_string(), url.trim().to_string()))
+            })
+            .collect()
+    }
+
+    /// Package names of the `Requires-Dist` entries in METADATA, extras-only requirements excluded
+    pub fn requires(&self) -> Vec<String> {
+        self.read("METADATA")
+            .unwrap_or_default()
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .filter_map(|line| line.strip_prefix("Requires-Dist:"))
+            .filter(|requirement| {
+                requirement
+                    .split_once(';')
+                    .is_none_or(|(_, marker)| !marker.contains("extra"))
+            })
+            .map(|requirement| requirement_name(requirement.trim()).to_string())
+            .collect()
+    }
+
+    /// The first project url whose label is one of `labels`, compared like PyPI does
+    pub fn project_url(&self, labels: &[&str]) -> Option<String> {
+        let normalize = |label: &str| {
+            label
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let urls = self.project_urls();
+        labels.iter().find_map(|label| {
+            urls.iter()
+                .find(|(name, _)| normalize(name) == normalize(label))
+                .map(|(_, url)| url.clone())
+        })
+    }
+
+    /// Importable top-level modules, from `top_level.txt` or else `RECORD`
+    pub fn top_level_modules(&self) -> Vec<String> {
+        if let Some(top_level) = self.read("top_level.txt") {
+            let modules: Vec<String> = top_level
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect();
+            if !modules.is_empty() {
+                return modules;
+            }
+        }
+
+        let mut modules: Vec<String> = Vec::new();
+        for line in self.read("RECORD").unwrap_or_default().lines() {
+            let path = line.split(',').next().unwrap_or_default();
+            let module = match path.split_once('/') {
+                Some((dir, _)) => {
+                    if dir.ends_with(".dist-info")
+                        || dir.ends_with(".data")
+                        || dir == "__pycache__"
+                        || dir == ".."
+                    {
+                        continue;
+                    }
+                    dir
+                }
+                None => match path.strip_suffix(".py") {
+                    Some(module) => module,
+                    None => continue,
+                },
+            };
+            if !modules.iter().any(|m| m == module) {
+                modules.push(module.to_string());
+            }
+        }
+        modules
+    }
+
+    /// Top-level modules excluding the `nonebot` namespace used by nonebot2 and adapters
+    pub fn plugin_modules(&self) -> Vec<String> {
+        self.top_level_modules()
+            .into_iter()
+            .filter(|module| module != "nonebot")
+            .collect()
+    }
+
+    /// The module to load as a plugin, the one named like the package if there are several
+    pub fn import_module(&self, package_name: &str) -> Option<String> {
+        let package_name = normalize_name(package_name);
+        let modules = self.plugin_modules();
+        modules
+            .iter()
+            .find(|module| normalize_name(module) == package_name)
+            .or_else(|| modules.first())
+            .cloned()
+    }
+
+    /// Whether the distribution is a NoneBot plugin
+    ///
+    /// Either it has a NoneBot classifier, or one of its top-level modules imports `nonebot.plugin`.
+    pub fn is_nonebot_plugin(&self) -> bool {
+        let modules = self.plugin_modules();
+        if modules.is_empty() {
+            return false;
+        }
+        if self
+            .classifiers()
+            .iter()
+            .any(|c| c.to_lowercase().contains("nonebot"))
+        {
+            return true;
+        }
+        modules.iter().any(|module| {
+            let package_init = self.site_packages.join(module).join("__init__.py");
+            let single_module = self.site_packages.join(format!("{module}.py"));
+            [package_init, single_module]
+                .iter()
+                .filter_map(|path| fs::read_to_string(path).ok())
+                .any(|source| {
+                    source.contains("nonebot.plugin") || source.contains("PluginMetadata")
+                })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn install(site_packages: &Path, dist: &str, files: &[(&str, &str)]) {
+        let dist_info = site_packages.join(format!("{dist}.dist-info"));
+        fs::create_dir_all(&dist_info).unwrap();
+        let mut record = String::new();
+        for (path, content) in files {
+            let file = site_packages.join(path);
+            if path.ends_with("top_level.txt") || path.ends_with("METADATA") {
+                fs::write(dist_info.join(path), content).unwrap();
+                continue;
+            }
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, content).unwrap();
+            record.push_str(&format!("{path},,\n"));
+        }
+        record.push_str(&format!("{dist}.dist-info/RECORD,,\n"));
+        fs::write(dist_info.join("RECORD"), record).unwrap();
+    }
+
+    #[test]
+    fn test_detect_plugin_distributions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let site_packages = temp_dir.path();
+        install(
+            site_packages,
+            "nonebot_plugin_status-0.9.0",
+            &[(
+                "nonebot_plugin_status/__init__.py",
+                "from nonebot.plugin import PluginMetadata",
+            )],
+        );
+        install(
+            site_packages,
+            "weather_bot-1.0.0",
+            &[
+                ("top_level.txt", "weather\n"),
+                ("weather/__init__.py", "from nonebot import require"),
+            ],
+        );
+        install(
+            site_packages,
+            "awesome_helper-1.0.0",
+            &[
+                (
+                    "METADATA",
+                    "Name: awesome-helper\nClassifier: Framework :: NoneBot\n\
+                    Project-URL: Source Code, https://github.com/owner/awesome\n\
+                    Project-URL: Release Notes, https://github.com/owner/awesome/releases\n\
+                    Requires-Dist: nonebot2>=2.4\n\
+                    Requires-Dist: nonebot-plugin-localstore[all] (>=0.7); python_version >= \"3.10\"\n\
+                    Requires-Dist: pytest; extra == \"test\"\n\nbody",
+                ),
+                ("awesome.py", ""),
+            ],
+        );
+        install(
+            site_packages,
+            "nonebot_plugin_utils-0.1.0",
+            &[("nonebot_plugin_utils/__init__.py", "import httpx")],
+        );
+        install(
+            site_packages,
+            "nonebot_adapter_onebot-2.4.6",
+            &[(
+                "nonebot/adapters/onebot/__init__.py",
+                "from nonebot.plugin import on",
+            )],
+        );
+
+        let find = |name| Distribution::find_in(site_packages, name).unwrap();
+        assert!(find("nonebot-plugin-status").is_nonebot_plugin());
+        assert!(find("Awesome-Helper").is_nonebot_plugin());
+        // 名字像插件的辅助库和适配器不是插件
+        assert!(!find("nonebot-plugin-utils").is_nonebot_plugin());
+        assert!(!find("nonebot-adapter-onebot").is_nonebot_plugin());
+        assert!(!find("weather-bot").is_nonebot_plugin());
+        assert_eq!(find("weather-bot").top_level_modules(), ["weather"]);
+        assert_eq!(find("awesome-helper").top_level_modules(), ["awesome"]);
+        assert_eq!(
+            find("awesome-helper")
+                .project_url(&["changelog", "release-notes"])
+                .as_deref(),
+            Some("https://github.com/owner/awesome/releases")
+        );
+        assert!(find("weather-bot").project_url(&["changelog"]).is_none());
+        assert_eq!(
+            find("awesome-helper").requires(),
+            ["nonebot2", "nonebot-plugin-localstore"]
+        );
+        assert!(find("weather-bot").requires().is_empty());
+        // 导入名与包名不同
+        assert_eq!(
+            find("weather-bot").import_module("weather-bot").as_deref(),
+            Some("weather")
+        );
+        assert_eq!(
+            find("nonebot-plugin-status")
+                .import_module("nonebot-plugin-status")
+                .as_deref(),
+            Some("nonebot_plugin_status")
+        );
+        assert!(
+            find("nonebot-adapter-onebot")
+                .import_module("nonebot-adapter-onebot")
+                .is_none()
+        );
+        assert!(Distribution::find_in(site_packages, "missing").is_none());
+    }
+
+    #[test]
+    fn test_local_path_distributions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let site_packages = temp_dir.path();
+        install(site_packages, "nonebot_plugin_foo-0.1.0", &[]);
+        let dist_info = site_packages.join("nonebot_plugin_foo-0.1.0.dist-info");
+        fs::write(
+            dist_info.join("direct_url.json"),
+            r#"{"url": "file:///home/dev/nonebot-plugin-foo", "dir_info": {"editable": true}}"#,
+        )
+        .unwrap();
+        let direct_url = Distribution::find_in(site_packages, "nonebot-plugin-foo")
+            .and_then(|d| d.direct_url())
+            .unwrap();
+        assert_eq!(
+            direct_url.local_path(),
+            Some(PathBuf::from("/home/dev/nonebot-plugin-foo"))
+        );
+        assert!(direct_url.is_editable());
+        // 路径中的空格和中文会被百分号编码
+        let encoded: DirectUrl = serde_json::from_str(
+            r#"{"url": "file:///home/dev/my%20plugins/%E5%A4%A9%E6%B0%94", "dir_info": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            encoded.local_path(),
+            Some(PathBuf::from("/home/dev/my plugins/天气"))
+        );
+        assert!(!encoded.is_editable());
+
+        // src 布局，包名与模块名不同
+        let package_dir = temp_dir.path().join("weather-bot");
+        fs::create_dir_all(package_dir.join("src/weather")).unwrap();
+        fs::create_dir_all(package_dir.join("tests")).unwrap();
+        fs::write(package_dir.join("src/weather/__init__.py"), "").unwrap();
+        fs::write(package_dir.join("tests/__init__.py"), "").unwrap();
+        assert_eq!(
+            source_module(&package_dir, "weather-bot").as_deref(),
+            Some("weather")
+        );
+        fs::write(package_dir.join("weather_bot.py"), "").unwrap();
+        assert_eq!(
+            source_module(&package_dir, "weather-bot").as_deref(),
+            Some("weather_bot")
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Git source module for nbr
+//!
+//! Parses git requirements (`git+<url>[@<ref>][#subdirectory=<dir>]`) and fetches the
+//! repository to read the real package name before installing it. Without the `git+` prefix
+//! only `.git` urls and repositories on known forges are git sources, other urls such as
+//! wheels are installed as direct urls.
+use crate::pyproject;
+use crate::utils::fs_utils::TempDir;
+use crate::utils::process_utils;
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::process::Output;
+use tracing::debug;
+
+pub const SCHEMES: [&str; 4] = ["https://", "http://", "ssh://", "file://"];
+/// Hosts whose urls are git repositories unless they point to an archive
+const FORGE_HOSTS: [&str; 5] = [
+    "github.com",
+    "gitlab.com",
+    "bitbucket.org",
+    "codeberg.org",
+    "gitee.com",
+];
+/// Suffixes of distributions and source archives
+const ARCHIVE_SUFFIXES: [&str; 5] = [".whl", ".tar.gz", ".tgz", ".tar.bz2", ".zip"];
+
+/// A package hosted in a git repository, e.g.
+/// `git+ssh://git@gitlab.com/owner/nonebot-plugin-abs.git@v1.0#subdirectory=plugin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    /// Repository url without the `git+` prefix, reference and fragment
+    pub url: String,
+    /// Branch, tag or commit
+    pub reference: Option<String>,
+    /// Directory of the package inside the repository
+    pub subdirectory: Option<String>,
+}
+
+impl GitSource {
+    /// Whether the input looks like a git requirement rather than a package name
+    ///
+    /// Requires a `git+` prefix, an `ssh://` url, a `.git` repository or a known forge host.
+    pub fn is_git(input: &str) -> bool {
+        if input.starts_with("git+") || input.starts_with("ssh://") {
+            return true;
+        }
+        let Some(scheme) = SCHEMES.iter().find(|scheme| input.starts_with(*scheme)) else {
+            return false;
+        };
+        let url = input.split(['#', '?']).next().unwrap_or(input);
+        if ARCHIVE_SUFFIXES.iter().any(|suffix| url.ends_with(suffix)) {
+            return false;
+        }
+        let mut segments = url[scheme.len()..].split('/');
+        // 去掉用户信息和端口
+        let host = segments.next().unwrap_or_default();
+        let host = host.rsplit('@').next().unwrap_or(host);
+        let host = host.split(':').next().unwrap_or(host);
+        FORGE_HOSTS.contains(&host.to_lowercase().as_str())
+            || segments.any(|segment| {
+                let repo = segment.split('@').next().unwrap_or(segment);
+                repo.ends_with(".git")
+            })
+    }
+
+    pub fn parse(input: &str) -> Result<Self> {
+        let url = input.strip_prefix("git+").unwrap_or(input);
+        let scheme_end = SCHEMES
+            .iter()
+            .find(|scheme| url.starts_with(*scheme))
+            .map(|scheme| scheme.len())
+            .with_context(|| format!("Unsupported git url: {}", input))?;
+
+        let (url, fragment) = match url.split_once('#') {
+            Some((url, fragment)) => (url, Some(fragment)),
+            None => (url, None),
+        };
+        let subdirectory = fragment
+            .into_iter()
+            .flat_map(|fragment| fragment.split('&'))
+            .find_map(|param| param.strip_prefix("subdirectory="))
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| dir.trim_matches('/').to_string());
+
+        // ssh://git@host/path 中的 @ 属于用户信息，引用只在路径部分查找
+        let path_start = url[scheme_end..]
+            .find('/')
+            .map(|i| scheme_end + i)
+            .with_context(|| format!("Missing repository path in git url: {}", input))?;
+        let (url, reference) = match url[path_start..].rfind('@') {
+            Some(i) => (
+                &url[..path_start + i],
+                Some(&url[path_start + i + 1..]).filter(|r| !r.is_empty()),
+            ),
+            None => (url, None),
+        };
+        if url[path_start..].trim_matches('/').is_empty() {
+            anyhow::bail!("Missing repository path in git url: {}", input);
+        }
+
+        Ok(Self {
+            url: url.to_string(),
+            reference: reference.map(str::to_string),
+            subdirectory,
+        })
+    }
+
+    /// Repository name, the last segment of the url without `.git`
+    pub fn repo_name(&self) -> &str {
+        let path = self.url.trim_end_matches('/');
+        let name = path.rsplit('/').next().unwrap_or(path);
+        name.strip_suffix(".git").unwrap_or(name)
+    }
+
+    /// Requirement passed to `uv add`
+    pub fn requirement(&self) -> String {
+        let mut requirement = format!("git+{}", self.url);
+        if let Some(ref reference) = self.reference {
+            requirement.push('@');
+            requirement.push_str(reference);
+        }
+        if let Some(ref subdirectory) = self.subdirectory {
+            requirement.push_str("#subdirectory=");
+            requirement.push_str(subdirectory);
+        }
+        requirement
+    }
+
+    /// Fetch the repository and read the package name from its `pyproject.toml`
+    pub async fn fetch_package_name(&self) -> Result<String> {
+        let checkout = TempDir::new("git")?;
+        self.fetch(checkout.path()).await?;
+
+        let package_dir = match self.subdirectory {
+            Some(ref subdirectory) => checkout.path().join(subdirectory),
+            None => checkout.path().to_path_buf(),
+        };
+        pyproject::project_name(&package_dir)
+            .with_context(|| format!("Failed to read the package name of {}", self.requirement()))
+    }
+
+    /// Package name `uv add` recorded for this repository in `tool.uv.sources`
+    pub fn installed_name(&self, work_dir: &Path) -> Option<String> {
+        let content = std::fs::read_to_string(work_dir.join("pyproject.toml")).ok()?;
+        let value: toml::Value = toml::from_str(&content).ok()?;
+        let sources = value.get("tool")?.get("uv")?.get("sources")?.as_table()?;
+        let normalize = |url: &str| {
+            let url = url
+                .strip_prefix("git+")
+                .unwrap_or(url)
+                .trim_end_matches('/');
+            url.strip_suffix(".git").unwrap_or(url).to_lowercase()
+        };
+        let url = normalize(&self.url);
+        sources
+            .iter()
+            .find(|(_, source)| {
+                source
+                    .get("git")
+                    .and_then(|git| git.as_str())
+                    .is_some_and(|git| normalize(git) == url)
+            })
+            .map(|(name, _)| name.clone())
+    }
+
+    async fn fetch(&self, dir: &Path) -> Result<()> {
+        git(dir, &["init", "-q"]).await?;
+        let reference = self.reference.as_deref().unwrap_or("HEAD");
+        // 浅拉取分支、标签或提交，服务端不允许按提交拉取时回退到完整拉取
+        if let Err(e) = git(dir, &["fetch", "-q", "--depth", "1", &self.url, reference]).await {
+            debug!(
+                "Shallow fetch failed, fetching the whole repository: {:#}",
+                e
+            );
+            let refspec = "+refs/heads/*:refs/remotes/origin/*";
+            git(dir, &["fetch", "-q", "--tags", &self.url, refspec]).await?;
+            let candidates = [reference.to_string(), format!("origin/{reference}")];
+            for candidate in &candidates {
+                if git(dir, &["checkout", "-q", candidate]).await.is_ok() {
+                    return Ok(());
+                }
+            }
+            anyhow::bail!("Reference '{}' not found in {}", reference, self.url);
+        }
+        git(dir, &["checkout", "-q", "FETCH_HEAD"]).await?;
+        Ok(())
+    }
+}
+
+async fn git(dir: &Path, args: &[&str]) -> Result<Output> {
+    const TIMEOUT_SECS: u64 = 120;
+    let dir = dir.to_string_lossy();
+    let mut git_args = vec!["-C", dir.as_ref()];
+    git_args.extend(args);
+    process_utils::execute_command_with_output("git", &git_args, None, TIMEOUT_SECS).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_source() {
+        let source = GitSource::parse(
+            "git+ssh://git@gitlab.com/owner/nonebot-plugin-abs.git@v1.0#subdirectory=plugin/",
+        )
+        .unwrap();
+        assert_eq!(
+            source.url,
+            "ssh://git@gitlab.com/owner/nonebot-plugin-abs.git"
+        );
+        assert_eq!(source.reference.as_deref(), Some("v1.0"));
+        assert_eq!(source.subdirectory.as_deref(), Some("plugin"));
+        assert_eq!(source.repo_name(), "nonebot-plugin-abs");
+        assert_eq!(
+            source.requirement(),
+            "git+ssh://git@gitlab.com/owner/nonebot-plugin-abs.git@v1.0#subdirectory=plugin"
+        );
+
+        let source = GitSource::parse("https://gitea.example.com/owner/weather").unwrap();
+        assert_eq!(source.reference, None);
+        assert_eq!(source.repo_name(), "weather");
+        assert_eq!(
+            source.requirement(),
+            "git+https://gitea.example.com/owner/weather"
+        );
+
+        assert!(GitSource::is_git("git+file:///tmp/repo.git"));
+        assert!(!GitSource::is_git("nonebot-plugin-status"));
+        assert!(GitSource::is_git("https://github.com/owner/weather"));
+        assert!(GitSource::is_git(
+            "https://git.example.com/owner/weather.git@v1.0"
+        ));
+        assert!(!GitSource::is_git(
+            "https://files.example.com/nonebot_plugin_weather-0.1.0-py3-none-any.whl"
+        ));
+        assert!(!GitSource::is_git(
+            "https://github.com/owner/weather/releases/download/v1.0/weather-1.0.tar.gz"
+        ));
+        assert!(!GitSource::is_git("https://example.com/owner/weather"));
+        assert!(GitSource::parse("git+ftp://example.com/repo").is_err());
+        assert!(GitSource::parse("git+https://github.com").is_err());
+    }
+
+    #[test]
+    fn test_installed_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("pyproject.toml"),
+            "[project]\nname = \"bot\"\n\n[tool.uv.sources]\n\
+            nonebot-plugin-weather = { git = \"https://github.com/owner/weather\", rev = \"v1.0\" }\n",
+        )
+        .unwrap();
+        let source = GitSource::parse("git+https://github.com/owner/weather.git@v1.0").unwrap();
+        assert_eq!(
+            source.installed_name(temp_dir.path()).as_deref(),
+            Some("nonebot-plugin-weather")
+        );
+        let source = GitSource::parse("git+https://github.com/owner/other").unwrap();
+        assert!(source.installed_name(temp_dir.path()).is_none());
+    }
+}
@@ -0,0 +1,228 @@
+//! Dependency graph module for nbr
+//!
+//! A small directed graph of package names, rendered as a text tree, Graphviz DOT or JSON.
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Directed graph, node -> nodes it points to
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    edges: BTreeMap<String, Vec<String>>,
+}
+
+impl DependencyGraph {
+    pub fn add_node(&mut self, node: &str) {
+        self.edges.entry(node.to_string()).or_default();
+    }
+
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        self.add_node(to);
+        let children = self.edges.entry(from.to_string()).or_default();
+        if !children.iter().any(|c| c == to) {
+            children.push(to.to_string());
+            children.sort();
+        }
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, node: &str) -> bool {
+        self.edges.contains_key(node)
+    }
+
+    pub fn children(&self, node: &str) -> &[String] {
+        self.edges.get(node).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Nodes pointed to by at least one other node
+    pub fn dependencies(&self) -> Vec<&str> {
+        self.edges
+            .keys()
+            .filter(|node| self.edges.values().any(|children| children.contains(node)))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The subgraph reachable from `roots`
+    pub fn reachable(&self, roots: &[impl AsRef<str>]) -> Self {
+        let mut graph = Self::default();
+        let mut stack: Vec<&str> = roots.iter().map(AsRef::as_ref).collect();
+        while let Some(node) = stack.pop() {
+            if graph.contains(node) {
+                continue;
+            }
+            let children = self.children(node);
+            graph.edges.insert(node.to_string(), children.to_vec());
+            stack.extend(children.iter().map(String::as_str));
+        }
+        graph
+    }
+
+    /// Nodes only reachable through `removed`, not from any of `roots`
+    pub fn orphans(&self, removed: &str, roots: &[impl AsRef<str>]) -> Vec<String> {
+        let kept = self.reachable(roots);
+        self.reachable(&[removed])
+            .nodes()
+            .filter(|node| *node != removed && !kept.contains(node))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// The same graph with every edge reversed
+    pub fn inverted(&self) -> Self {
+        let mut inverted = Self::default();
+        for (from, children) in &self.edges {
+            inverted.add_node(from);
+            for to in children {
+                inverted.add_edge(to, from);
+            }
+        }
+        inverted
+    }
+
+    /// Render the subtrees of `roots` with box-drawing characters
+    pub fn render_tree(&self, roots: &[String]) -> String {
+        let mut output = String::new();
+        for root in roots {
+            output.push_str(root);
+            output.push('\n');
+            self.render_children(root, "", &mut vec![root.as_str()], &mut output);
+        }
+        output
+    }
+
+    fn render_children<'a>(
+        &'a self,
+        node: &str,
+        prefix: &str,
+        path: &mut Vec<&'a str>,
+        output: &mut String,
+    ) {
+        let children = self.children(node);
+        for (i, child) in children.iter().enumerate() {
+            let last = i == children.len() - 1;
+            let branch = if last { "└── " } else { "├── " };
+            // 循环依赖时不再展开
+            if path.contains(&child.as_str()) {
+                let _ = writeln!(output, "{prefix}{branch}{child} (cycle)");
+                continue;
+            }
+            let _ = writeln!(output, "{prefix}{branch}{child}");
+            let child_prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            path.push(child);
+            self.render_children(child, &child_prefix, path, output);
+            path.pop();
+        }
+    }
+
+    /// Graphviz DOT of the whole graph
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut output = format!("digraph \"{name}\" {{\n");
+        for (from, children) in &self.edges {
+            if children.is_empty() {
+                let _ = writeln!(output, "    \"{from}\";");
+            }
+            for to in children {
+                let _ = writeln!(output, "    \"{from}\" -> \"{to}\";");
+            }
+        }
+        output.push_str("}\n");
+        output
+    }
+
+    /// Nested JSON of the subtrees of `roots`, children are stored under `key`
+    pub fn to_json(&self, roots: &[String], key: &str) -> Value {
+        Value::Array(
+            roots
+                .iter()
+                .map(|root| self.node_json(root, key, &mut vec![root.as_str()]))
+                .collect(),
+        )
+    }
+
+    fn node_json<'a>(&'a self, node: &str, key: &str, path: &mut Vec<&'a str>) -> Value {
+        let children: Vec<Value> = self
+            .children(node)
+            .iter()
+            .map(|child| {
+                if path.contains(&child.as_str()) {
+                    return json!({ "name": child, "cycle": true });
+                }
+                path.push(child);
+                let value = self.node_json(child, key, path);
+                path.pop();
+                value
+            })
+            .collect();
+        json!({ "name": node, key: children })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph() -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        graph.add_edge("plugin-a", "plugin-localstore");
+        graph.add_edge("plugin-a", "adapter-onebot");
+        graph.add_edge("plugin-b", "plugin-localstore");
+        graph.add_edge("plugin-localstore", "plugin-a");
+        graph
+    }
+
+    #[test]
+    fn test_render_tree() {
+        let graph = graph();
+        let tree = graph.render_tree(&["plugin-a".to_string()]);
+        assert_eq!(
+            tree,
+            "plugin-a\n├── adapter-onebot\n└── plugin-localstore\n    └── plugin-a (cycle)\n"
+        );
+
+        let inverted = graph.inverted();
+        let tree = inverted.render_tree(&["plugin-localstore".to_string()]);
+        assert_eq!(
+            tree,
+            "plugin-localstore\n├── plugin-a\n│   └── plugin-localstore (cycle)\n└── plugin-b\n"
+        );
+        assert_eq!(
+            graph.dependencies(),
+            ["adapter-onebot", "plugin-a", "plugin-localstore"]
+        );
+    }
+
+    #[test]
+    fn test_dot_and_json() {
+        let graph = graph();
+        let dot = graph.reachable(&["plugin-b".to_string()]).to_dot("plugins");
+        assert!(dot.starts_with("digraph \"plugins\" {\n"));
+        assert!(dot.contains("    \"plugin-b\" -> \"plugin-localstore\";\n"));
+        assert!(dot.contains("    \"adapter-onebot\";\n"));
+        assert!(
+            !graph
+                .reachable(&["adapter-onebot".to_string()])
+                .contains("plugin-a")
+        );
+
+        let mut graph = graph;
+        graph.add_edge("plugin-c", "plugin-htmlrender");
+        graph.add_edge("plugin-htmlrender", "plugin-localstore");
+        assert_eq!(
+            graph.orphans("plugin-c", &["plugin-b"]),
+            ["plugin-htmlrender"]
+        );
+        assert!(graph.orphans("plugin-a", &["plugin-b"]).is_empty());
+
+        let json = graph.to_json(&["plugin-b".to_string()], "dependencies");
+        assert_eq!(json[0]["name"], "plugin-b");
+        assert_eq!(json[0]["dependencies"][0]["name"], "plugin-localstore");
+        assert_eq!(
+            json[0]["dependencies"][0]["dependencies"][0]["dependencies"][1]["cycle"],
+            true
+        );
+    }
+}
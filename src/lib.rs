@@ -1,7 +1,19 @@
+pub mod changelog;
+pub mod check;
 pub mod cli;
 pub mod config;
+pub mod dist;
 pub mod error;
+pub mod git;
+pub mod graph;
+pub mod lock;
 pub mod log;
+pub mod prompt;
+pub mod publish;
 pub mod pyproject;
+pub mod registry;
+pub mod search;
+pub mod transaction;
 pub mod utils;
 pub mod uv;
+pub mod version;
@@ -0,0 +1,226 @@
+//! Lock file module for nbr
+//!
+//! Compares two versions of `uv.lock` package by package, used to preview upgrades.
+use crate::log::StyledText;
+use std::collections::BTreeMap;
+
+/// A changed line of a `[[package]]` block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Added(String),
+    Removed(String),
+}
+
+/// A package added, removed or changed in the lock file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageChange {
+    pub name: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+    pub lines: Vec<DiffLine>,
+}
+
+impl PackageChange {
+    pub fn display(&self) {
+        StyledText::new(" ")
+            .with(|text| {
+                match (&self.old_version, &self.new_version) {
+                    (Some(old), Some(new)) => text
+                        .yellow_bold("~")
+                        .cyan_bold(&self.name)
+                        .text(format!("{old} → {new}")),
+                    (None, Some(new)) => text.green_bold("+").cyan_bold(&self.name).text(new),
+                    (Some(old), None) => text.red_bold("-").cyan_bold(&self.name).text(old),
+                    (None, None) => text.yellow_bold("~").cyan_bold(&self.name),
+                };
+            })
+            .println();
+        for line in &self.lines {
+            let mut text = StyledText::new("");
+            match line {
+                DiffLine::Added(line) => text.green(format!("    + {line}")),
+                DiffLine::Removed(line) => text.red(format!("    - {line}")),
+            }
+            .println();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockDiff {
+    pub changes: Vec<PackageChange>,
+}
+
+impl LockDiff {
+    pub fn new(before: &str, after: &str) -> Self {
+        let before = packages(before);
+        let after = packages(after);
+        let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+        names.sort();
+        names.dedup();
+
+        let changes = names
+            .into_iter()
+            .filter_map(|name| {
+                let old = before.get(name);
+                let new = after.get(name);
+                if old == new {
+                    return None;
+                }
+                let old_lines: Vec<&str> = old.map(|b| b.lines().collect()).unwrap_or_default();
+                let new_lines: Vec<&str> = new.map(|b| b.lines().collect()).unwrap_or_default();
+                Some(PackageChange {
+                    name: name.clone(),
+                    old_version: old.and_then(|b| field(b, "version")),
+                    new_version: new.and_then(|b| field(b, "version")),
+                    lines: diff_lines(&old_lines, &new_lines),
+                })
+            })
+            .collect();
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn display(&self) {
+        self.changes.iter().for_each(PackageChange::display);
+    }
+}
+
+/// `[[package]]` blocks by name, a package locked in several versions is keyed `name@version`
+fn packages(lock: &str) -> BTreeMap<String, &str> {
+    let mut packages = BTreeMap::new();
+    for block in lock.split("[[package]]\n").skip(1) {
+        // 块之间的空行不计入差异
+        let block = block.trim_end();
+        let Some(name) = field(block, "name") else {
+            continue;
+        };
+        let key = if packages.contains_key(&name) {
+            format!("{}@{}", name, field(block, "version").unwrap_or_default())
+        } else {
+            name
+        };
+        packages.insert(key, block);
+    }
+    packages
+}
+
+/// A top-level `key = "value"` of a package block
+fn field(block: &str, key: &str) -> Option<String> {
+    block.lines().find_map(|line| {
+        let value = line.strip_prefix(key)?.trim_start().strip_prefix('=')?;
+        Some(value.trim().trim_matches('"').to_string())
+    })
+}
+
+/// Added and removed lines, based on the longest common subsequence
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::Removed(old[i].to_string()));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new[j].to_string()));
+            j += 1;
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = r#"version = 1
+requires-python = ">=3.10"
+
+[[package]]
+name = "nonebot-plugin-status"
+version = "0.9.0"
+source = { registry = "https://pypi.org/simple" }
+dependencies = [
+    { name = "psutil" },
+]
+wheels = [
+    { url = "https://files/nonebot_plugin_status-0.9.0-py3-none-any.whl" },
+]
+
+[[package]]
+name = "psutil"
+version = "6.1.0"
+source = { registry = "https://pypi.org/simple" }
+
+[[package]]
+name = "typing-extensions"
+version = "4.12.2"
+"#;
+
+    #[test]
+    fn test_lock_diff() {
+        let after = BEFORE.replace("0.9.0", "0.10.0").replace(
+            "    { name = \"psutil\" },\n",
+            "    { name = \"psutil\" },\n    { name = \"humanize\" },\n",
+        ) + "\n[[package]]\nname = \"humanize\"\nversion = \"4.11.0\"\n";
+        let after = after.replace(
+            "[[package]]\nname = \"typing-extensions\"\nversion = \"4.12.2\"\n",
+            "",
+        );
+
+        let diff = LockDiff::new(BEFORE, &after);
+        let summary: Vec<_> = diff
+            .changes
+            .iter()
+            .map(|c| {
+                (
+                    c.name.as_str(),
+                    c.old_version.as_deref(),
+                    c.new_version.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("humanize", None, Some("4.11.0")),
+                ("nonebot-plugin-status", Some("0.9.0"), Some("0.10.0")),
+                ("typing-extensions", Some("4.12.2"), None),
+            ]
+        );
+        assert_eq!(
+            diff.changes[1].lines,
+            [
+                DiffLine::Removed("version = \"0.9.0\"".to_string()),
+                DiffLine::Added("version = \"0.10.0\"".to_string()),
+                DiffLine::Added("    { name = \"humanize\" },".to_string()),
+                DiffLine::Removed(
+                    "    { url = \"https://files/nonebot_plugin_status-0.9.0-py3-none-any.whl\" },"
+                        .to_string()
+                ),
+                DiffLine::Added(
+                    "    { url = \"https://files/nonebot_plugin_status-0.10.0-py3-none-any.whl\" },"
+                        .to_string()
+                ),
+            ]
+        );
+        assert!(LockDiff::new(BEFORE, BEFORE).is_empty());
+    }
+}
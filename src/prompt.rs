@@ -0,0 +1,371 @@
+//! Interactive prompt module for nbr
+//!
+//! Every prompt goes through the builders in this module instead of calling `dialoguer`
+//! directly. In non-interactive mode (`--no-input`, or `NBR_NO_INPUT=1`) every prompt takes
+//! its default and a prompt without a default fails with a clear error, `--yes` additionally
+//! answers every confirmation yes. Tests can script the answers with [`script_answers`].
+use anyhow::{Context, Result};
+use dialoguer::theme::ColorfulTheme;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable enabling non-interactive mode
+pub const NO_INPUT_ENV: &str = "NBR_NO_INPUT";
+
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    static SCRIPTED_ANSWERS: RefCell<VecDeque<Answer>> = const { RefCell::new(VecDeque::new()) };
+}
+
+/// A scripted answer, consumed by the next prompt in order
+#[derive(Debug, Clone, PartialEq)]
+pub enum Answer {
+    Confirm(bool),
+    Select(usize),
+    MultiSelect(Vec<usize>),
+    Input(String),
+    /// Answer as in non-interactive mode
+    NonInteractive,
+    /// Answer as with `--yes`, confirmations yes and other prompts their defaults
+    AssumeYes,
+}
+
+/// Enable or disable non-interactive mode
+pub fn set_no_input(no_input: bool) {
+    NO_INPUT.store(no_input, Ordering::Relaxed);
+}
+
+/// Enable or disable answering every confirmation yes, implies non-interactive mode
+pub fn set_assume_yes(assume_yes: bool) {
+    ASSUME_YES.store(assume_yes, Ordering::Relaxed);
+}
+
+/// Whether prompts are answered without asking
+pub fn is_no_input() -> bool {
+    NO_INPUT.load(Ordering::Relaxed)
+        || ASSUME_YES.load(Ordering::Relaxed)
+        || std::env::var(NO_INPUT_ENV)
+            .is_ok_and(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+}
+
+/// Script the answers of the following prompts on the current thread
+pub fn script_answers(answers: impl IntoIterator<Item = Answer>) {
+    SCRIPTED_ANSWERS.with(|scripted| scripted.borrow_mut().extend(answers));
+}
+
+/// The next scripted answer, [`Answer::AssumeYes`] with `--yes` and
+/// [`Answer::NonInteractive`] in non-interactive mode
+fn next_answer() -> Option<Answer> {
+    SCRIPTED_ANSWERS
+        .with(|scripted| scripted.borrow_mut().pop_front())
+        .or_else(|| {
+            if ASSUME_YES.load(Ordering::Relaxed) {
+                Some(Answer::AssumeYes)
+            } else {
+                is_no_input().then_some(Answer::NonInteractive)
+            }
+        })
+}
+
+fn missing_default(prompt: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "'{}' has no default answer, pass it as an argument when running non-interactively",
+        prompt
+    )
+}
+
+fn unexpected_answer(prompt: &str, answer: Answer) -> anyhow::Error {
+    anyhow::anyhow!("Unexpected scripted answer {:?} for '{}'", answer, prompt)
+}
+
+/// Yes / no question
+pub struct Confirm {
+    prompt: String,
+    default: bool,
+}
+
+impl Confirm {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            default: false,
+        }
+    }
+
+    pub fn default(mut self, default: bool) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Takes the default in non-interactive mode, only `--yes` confirms prompts defaulting to no
+    pub fn interact(self) -> Result<bool> {
+        match next_answer() {
+            Some(Answer::Confirm(answer)) => return Ok(answer),
+            Some(Answer::NonInteractive) => return Ok(self.default),
+            Some(Answer::AssumeYes) => return Ok(true),
+            Some(answer) => return Err(unexpected_answer(&self.prompt, answer)),
+            None => {}
+        }
+        dialoguer::Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(&self.prompt)
+            .default(self.default)
+            .interact()
+            .with_context(|| format!("Failed to prompt '{}'", self.prompt))
+    }
+}
+
+/// Choose one item
+pub struct Select<'a, T: Display> {
+    prompt: String,
+    items: &'a [T],
+    default: Option<usize>,
+}
+
+impl<'a, T: Display> Select<'a, T> {
+    pub fn new(prompt: impl Into<String>, items: &'a [T]) -> Self {
+        Self {
+            prompt: prompt.into(),
+            items,
+            default: None,
+        }
+    }
+
+    pub fn default(mut self, default: usize) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Returns the index of the selected item
+    pub fn interact(self) -> Result<usize> {
+        match next_answer() {
+            Some(Answer::Select(index)) if index < self.items.len() => return Ok(index),
+            Some(Answer::NonInteractive | Answer::AssumeYes) => {
+                return self.default.ok_or_else(|| missing_default(&self.prompt));
+            }
+            Some(answer) => return Err(unexpected_answer(&self.prompt, answer)),
+            None => {}
+        }
+        let theme = ColorfulTheme::default();
+        let mut select = dialoguer::Select::with_theme(&theme);
+        select = select.with_prompt(&self.prompt).items(self.items);
+        if let Some(default) = self.default {
+            select = select.default(default);
+        }
+        select
+            .interact()
+            .with_context(|| format!("Failed to prompt '{}'", self.prompt))
+    }
+}
+
+/// Choose any number of items
+pub struct MultiSelect<'a, T: Display> {
+    prompt: String,
+    items: &'a [T],
+    defaults: Option<Vec<bool>>,
+}
+
+impl<'a, T: Display> MultiSelect<'a, T> {
+    pub fn new(prompt: impl Into<String>, items: &'a [T]) -> Self {
+        Self {
+            prompt: prompt.into(),
+            items,
+            defaults: None,
+        }
+    }
+
+    /// Items checked by default, a prompt without defaults requires a choice
+    pub fn defaults(mut self, defaults: &[bool]) -> Self {
+        self.defaults = Some(defaults.to_vec());
+        self
+    }
+
+    /// Returns the indexes of the selected items
+    pub fn interact(self) -> Result<Vec<usize>> {
+        match next_answer() {
+            Some(Answer::MultiSelect(indexes)) if indexes.iter().all(|i| *i < self.items.len()) => {
+                return Ok(indexes);
+            }
+            Some(Answer::NonInteractive | Answer::AssumeYes) => {
+                let defaults = self.defaults.ok_or_else(|| missing_default(&self.prompt))?;
+                return Ok(defaults
+                    .iter()
+                    .take(self.items.len())
+                    .enumerate()
+                    .filter_map(|(i, checked)| checked.then_some(i))
+                    .collect());
+            }
+            Some(answer) => return Err(unexpected_answer(&self.prompt, answer)),
+            None => {}
+        }
+        let theme = ColorfulTheme::default();
+        let mut select = dialoguer::MultiSelect::with_theme(&theme);
+        select = select.with_prompt(&self.prompt).items(self.items);
+        if let Some(ref defaults) = self.defaults {
+            select = select.defaults(defaults);
+        }
+        select
+            .interact()
+            .with_context(|| format!("Failed to prompt '{}'", self.prompt))
+    }
+}
+
+type Validator<'a> = Box<dyn Fn(&str) -> Result<()> + 'a>;
+
+/// Free text input
+pub struct Input<'a> {
+    prompt: String,
+    default: Option<String>,
+    validator: Option<Validator<'a>>,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            default: None,
+            validator: None,
+        }
+    }
+
+    pub fn default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn validate_with(mut self, validator: impl Fn(&str) -> Result<()> + 'a) -> Self {
+        self.validator = Some(Box::new(validator));
+        self
+    }
+
+    fn validate(&self, input: String) -> Result<String> {
+        if let Some(ref validator) = self.validator {
+            validator(&input).with_context(|| format!("Invalid input for '{}'", self.prompt))?;
+        }
+        Ok(input)
+    }
+
+    pub fn interact_text(self) -> Result<String> {
+        match next_answer() {
+            Some(Answer::Input(input)) => return self.validate(input),
+            Some(Answer::NonInteractive | Answer::AssumeYes) => {
+                let default = self
+                    .default
+                    .clone()
+                    .ok_or_else(|| missing_default(&self.prompt))?;
+                return self.validate(default);
+            }
+            Some(answer) => return Err(unexpected_answer(&self.prompt, answer)),
+            None => {}
+        }
+        let theme = ColorfulTheme::default();
+        let mut input = dialoguer::Input::<String>::with_theme(&theme);
+        input = input.with_prompt(&self.prompt);
+        if let Some(ref default) = self.default {
+            input = input.default(default.clone());
+        }
+        if let Some(ref validator) = self.validator {
+            input = input.validate_with(|input: &String| -> Result<(), String> {
+                validator(input).map_err(|e| e.to_string())
+            });
+        }
+        input
+            .interact_text()
+            .with_context(|| format!("Failed to prompt '{}'", self.prompt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_answers() {
+        script_answers([
+            Answer::Confirm(false),
+            Answer::Select(1),
+            Answer::MultiSelect(vec![0, 2]),
+            Answer::Input("awesome-bot".to_string()),
+        ]);
+        assert!(!Confirm::new("Continue?").default(true).interact().unwrap());
+        assert_eq!(Select::new("Pick", &["a", "b"]).interact().unwrap(), 1);
+        assert_eq!(
+            MultiSelect::new("Pick", &["a", "b", "c"])
+                .interact()
+                .unwrap(),
+            vec![0, 2]
+        );
+        let input = Input::new("Name")
+            .validate_with(|input| {
+                if input.contains(' ') {
+                    anyhow::bail!("no spaces")
+                }
+                Ok(())
+            })
+            .interact_text()
+            .unwrap();
+        assert_eq!(input, "awesome-bot");
+
+        // 脚本答案类型不匹配时报错
+        script_answers([Answer::Select(0)]);
+        assert!(Confirm::new("Continue?").interact().is_err());
+    }
+
+    #[test]
+    fn test_no_input_defaults() {
+        // 与 --no-input 相同的应答方式，不修改全局状态
+        script_answers(std::iter::repeat_n(Answer::NonInteractive, 9));
+        assert!(
+            !Confirm::new("Uninstall?")
+                .default(false)
+                .interact()
+                .unwrap()
+        );
+        assert!(Confirm::new("Continue?").default(true).interact().unwrap());
+        assert_eq!(
+            Select::new("Pick", &["a", "b"])
+                .default(1)
+                .interact()
+                .unwrap(),
+            1
+        );
+        assert!(Select::new("Pick", &["a", "b"]).interact().is_err());
+        assert_eq!(
+            MultiSelect::new("Pick", &["a", "b", "c"])
+                .defaults(&[true, false, true])
+                .interact()
+                .unwrap(),
+            vec![0, 2]
+        );
+        // 必选项没有默认值时报错
+        assert!(MultiSelect::new("Pick", &["a", "b"]).interact().is_err());
+        assert!(Input::new("Name").interact_text().is_err());
+        assert_eq!(
+            Input::new("Name").default("bot").interact_text().unwrap(),
+            "bot"
+        );
+    }
+
+    #[test]
+    fn test_assume_yes() {
+        // --yes 确认所有询问，其余询问仍使用默认值
+        script_answers(std::iter::repeat_n(Answer::AssumeYes, 3));
+        assert!(
+            Confirm::new("Uninstall?")
+                .default(false)
+                .interact()
+                .unwrap()
+        );
+        assert_eq!(
+            Select::new("Pick", &["a", "b"])
+                .default(1)
+                .interact()
+                .unwrap(),
+            1
+        );
+        assert!(Input::new("Name").interact_text().is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! Plugin publish module for nbr
+//!
+//! Builds the registry submission of a plugin project, as the JSON payload of the registry
+//! and as the body of a publish issue in the nonebot2 repository.
+use crate::check::{PLUGIN_TYPES, PluginProject};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Color of tags given without one
+pub const DEFAULT_TAG_COLOR: &str = "#ea5252";
+/// Limits of the registry on tags
+const MAX_TAGS: usize = 3;
+const MAX_TAG_LABEL_LEN: usize = 10;
+
+/// Issue form for publishing plugins to the registry
+pub const PUBLISH_ISSUE_URL: &str =
+    "https://github.com/nonebot/nonebot2/issues/new?template=plugin_publish.yml";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub label: String,
+    pub color: String,
+}
+
+impl Tag {
+    /// Parse `label` or `label:#rrggbb`
+    pub fn parse(input: &str) -> Result<Self> {
+        let (label, color) = match input.split_once(':') {
+            Some((label, color)) => (label.trim(), color.trim()),
+            None => (input.trim(), DEFAULT_TAG_COLOR),
+        };
+        if label.is_empty() || label.chars().count() > MAX_TAG_LABEL_LEN {
+            anyhow::bail!(
+                "Tag label '{}' must be 1 to {} characters",
+                label,
+                MAX_TAG_LABEL_LEN
+            );
+        }
+        let color_re = Regex::new(r"^#[0-9a-fA-F]{6}$").context("Invalid regex pattern")?;
+        if !color_re.is_match(color) {
+            anyhow::bail!("Tag color '{}' must look like #ea5252", color);
+        }
+        Ok(Self {
+            label: label.to_string(),
+            color: color.to_lowercase(),
+        })
+    }
+}
+
+/// Registry fields of a plugin to submit, named as in `RegistryPlugin`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RegistrySubmission {
+    pub module_name: String,
+    pub project_link: String,
+    pub name: String,
+    pub desc: String,
+    pub homepage: String,
+    pub tags: Vec<Tag>,
+    #[serde(rename = "type")]
+    pub plugin_type: String,
+    pub supported_adapters: Option<Vec<String>>,
+}
+
+impl RegistrySubmission {
+    /// Collect the fields from `__plugin_meta__`, falling back to `project` metadata
+    pub fn from_project(project: &PluginProject, tags: Vec<Tag>) -> Result<Self> {
+        if tags.len() > MAX_TAGS {
+            anyhow::bail!("At most {} tags are allowed", MAX_TAGS);
+        }
+        let meta = project.plugin_meta().with_context(|| {
+            format!(
+                "__plugin_meta__ = PluginMetadata(...) not found in {}",
+                project.module_name
+            )
+        })?;
+
+        let name = meta.string("name").map(str::to_string);
+        let desc = meta
+            .string("description")
+            .filter(|desc| !desc.trim().is_empty())
+            .or(project.project.description.as_deref())
+            .filter(|desc| !desc.trim().is_empty())
+            .map(str::to_string);
+        let homepage = meta
+            .string("homepage")
+            .map(str::to_string)
+            .or_else(|| project_homepage(project));
+        let plugin_type = meta
+            .string("type")
+            .filter(|t| PLUGIN_TYPES.contains(t))
+            .map(str::to_string);
+
+        let missing: Vec<&str> = [
+            ("name", name.is_none()),
+            ("description", desc.is_none()),
+            ("homepage", homepage.is_none()),
+            ("type", plugin_type.is_none()),
+        ]
+        .into_iter()
+        .filter(|(_, missing)| *missing)
+        .map(|(field, _)| field)
+        .collect();
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Missing required fields for the registry: {}, set them in PluginMetadata",
+                missing.join(", ")
+            );
+        }
+
+        Ok(Self {
+            module_name: project.module_name.clone(),
+            project_link: project.project_link.clone(),
+            name: name.unwrap_or_default(),
+            desc: desc.unwrap_or_default(),
+            homepage: homepage.unwrap_or_default(),
+            tags,
+            plugin_type: plugin_type.unwrap_or_default(),
+            supported_adapters: meta
+                .value("supported_adapters")
+                .and_then(supported_adapters),
+        })
+    }
+
+    pub fn issue_title(&self) -> String {
+        format!("Plugin: {}", self.name)
+    }
+
+    /// Markdown body of the publish issue
+    pub fn issue_body(&self) -> Result<String> {
+        let adapters = match self.supported_adapters {
+            Some(ref adapters) => adapters.join(", "),
+            None => "All adapters".to_string(),
+        };
+        let fields = [
+            ("插件名称", self.name.clone()),
+            ("插件描述", self.desc.clone()),
+            ("PyPI 项目名", self.project_link.clone()),
+            ("插件 import 包名", self.module_name.clone()),
+            ("插件项目仓库/主页链接", self.homepage.clone()),
+            ("插件类型", self.plugin_type.clone()),
+            ("支持的适配器", adapters),
+            ("标签", serde_json::to_string(&self.tags)?),
+        ];
+        let mut body = String::new();
+        for (title, value) in fields {
+            let _ = write!(body, "### {title}\n\n{value}\n\n");
+        }
+        Ok(body)
+    }
+}
+
+/// `Homepage` or `Repository` in `project.urls`
+fn project_homepage(project: &PluginProject) -> Option<String> {
+    ["homepage", "repository", "source"].iter().find_map(|key| {
+        project
+            .project
+            .urls
+            .iter()
+            .find(|(name, _)| name.to_lowercase() == *key)
+            .map(|(_, url)| url.clone())
+    })
+}
+
+/// Adapter modules of a literal `supported_adapters`, `~` is short for `nonebot.adapters.`
+fn supported_adapters(value: &str) -> Option<Vec<String>> {
+    if value == "None" {
+        return None;
+    }
+    if !value.starts_with(['{', '[', '(']) {
+        warn!(
+            "supported_adapters '{}' is not a literal, it is resolved when the registry loads the plugin",
+            value
+        );
+        return None;
+    }
+    let re = Regex::new(r#"["']([\w.~]+)["']"#).ok()?;
+    let mut adapters: Vec<String> = re
+        .captures_iter(value)
+        .map(|captures| match captures[1].strip_prefix('~') {
+            Some(adapter) => format!("nonebot.adapters.{adapter}"),
+            None => captures[1].to_string(),
+        })
+        .collect();
+    adapters.sort();
+    adapters.dedup();
+    Some(adapters)
+}
+
+/// Distributions in `dist_dir` written since `since`, the output of the last `uv build`
+pub fn built_files(dist_dir: &Path, since: SystemTime) -> Result<Vec<PathBuf>> {
+    // 部分文件系统的修改时间精度较低，留出一秒余量
+    let since = since.checked_sub(Duration::from_secs(1)).unwrap_or(since);
+    let mut files: Vec<PathBuf> = fs::read_dir(dist_dir)
+        .with_context(|| format!("Failed to read {}", dist_dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.to_string_lossy();
+            name.ends_with(".whl") || name.ends_with(".tar.gz")
+        })
+        .filter(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= since)
+        })
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        anyhow::bail!("No distributions built in {}", dist_dir.display());
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(dir: &Path, init: &str) -> PluginProject {
+        fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nname = \"nonebot-plugin-weather\"\nversion = \"0.1.0\"\n\
+            description = \"Weather forecast\"\nrequires-python = \">=3.10\"\ndependencies = []\n\n\
+            [project.urls]\nRepository = \"https://github.com/owner/nonebot-plugin-weather\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("nonebot_plugin_weather.py"), init).unwrap();
+        PluginProject::load(dir).unwrap()
+    }
+
+    #[test]
+    fn test_registry_submission() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let init = r#"
+__plugin_meta__ = PluginMetadata(
+    name="天气, 预报",
+    description="",
+    usage="/天气",
+    type="application",
+    homepage=None,
+    supported_adapters={"~onebot.v11", "nonebot.adapters.qq"},
+)
+"#;
+        let plugin = project(temp_dir.path(), init);
+        let tags = vec![
+            Tag::parse("天气").unwrap(),
+            Tag::parse("工具:#AEEAA8").unwrap(),
+        ];
+        let submission = RegistrySubmission::from_project(&plugin, tags).unwrap();
+        assert_eq!(submission.name, "天气, 预报");
+        assert_eq!(submission.desc, "Weather forecast");
+        assert_eq!(
+            submission.homepage,
+            "https://github.com/owner/nonebot-plugin-weather"
+        );
+        assert_eq!(
+            submission.supported_adapters.as_deref(),
+            Some(
+                [
+                    "nonebot.adapters.onebot.v11".to_string(),
+                    "nonebot.adapters.qq".to_string()
+                ]
+                .as_slice()
+            )
+        );
+        assert_eq!(submission.tags[1].color, "#aeeaa8");
+
+        let json = serde_json::to_value(&submission).unwrap();
+        assert_eq!(json["module_name"], "nonebot_plugin_weather");
+        assert_eq!(json["type"], "application");
+        let body = submission.issue_body().unwrap();
+        assert!(body.contains("### PyPI 项目名\n\nnonebot-plugin-weather\n\n"));
+        assert!(body.contains(r##"[{"label":"天气","color":"#ea5252"}"##));
+
+        let plugin = project(
+            temp_dir.path(),
+            "__plugin_meta__ = PluginMetadata(description=\"\", supported_adapters=None)",
+        );
+        let error = RegistrySubmission::from_project(&plugin, vec![]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Missing required fields for the registry: name, type, set them in PluginMetadata"
+        );
+        assert!(Tag::parse("a-very-long-tag").is_err());
+        assert!(Tag::parse("天气:red").is_err());
+    }
+}
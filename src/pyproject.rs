@@ -6,6 +6,21 @@ use std::{
 };
 use toml_edit::{Array, Document, DocumentMut, InlineTable, Table};
 
+/// Read `project.name` from the `pyproject.toml` in `dir`, any package not only a NoneBot project
+pub fn project_name(dir: &Path) -> Result<String> {
+    let toml_path = dir.join("pyproject.toml");
+    let content = std::fs::read_to_string(&toml_path)
+        .with_context(|| format!("Failed to read {}", toml_path.display()))?;
+    let value: toml::Value = toml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", toml_path.display()))?;
+    value
+        .get("project")
+        .and_then(|project| project.get("name"))
+        .and_then(|name| name.as_str())
+        .map(str::to_string)
+        .with_context(|| format!("No project.name in {}", toml_path.display()))
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
 pub struct PyProjectConfig {
@@ -260,6 +275,34 @@ impl NbTomlEditor {
         item.as_array_mut().context("plugins is not an array")
     }
 
+    /// tool.nonebot.plugin_dirs, created when missing
+    fn plugin_dirs_array_mut(&mut self) -> Result<&mut Array> {
+        let table = self.nonebot_table_mut()?;
+        table
+            .entry("plugin_dirs")
+            .or_insert(toml_edit::value(Array::new()))
+            .as_array_mut()
+            .context("plugin_dirs is not an array")
+    }
+
+    /// tool.nbr.disabled-plugins, created when missing
+    fn disabled_plugins_array_mut(&mut self) -> Result<&mut Array> {
+        let tool = self.doc_mut["tool"]
+            .as_table_mut()
+            .context("tool is not a table")?;
+        let nbr = tool
+            .entry("nbr")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .context("tool.nbr is not a table")?;
+        // 只有 [tool.nbr.registry] 时 [tool.nbr] 是隐式表，写入键前需要显式输出
+        nbr.set_implicit(false);
+        nbr.entry("disabled-plugins")
+            .or_insert(toml_edit::value(Array::new()))
+            .as_array_mut()
+            .context("disabled-plugins is not an array")
+    }
+
     fn save(&self) -> Result<()> {
         std::fs::write(self.toml_path.clone(), self.doc_mut.to_string())?;
         Ok(())
@@ -329,6 +372,57 @@ impl NbTomlEditor {
             }
         });
         Self::fmt_toml_array(plugins_arr_mut);
+        // 卸载的插件也不再保留禁用状态
+        if let Some(disabled) = self.doc_mut["tool"]
+            .get_mut("nbr")
+            .and_then(|nbr| nbr.get_mut("disabled-plugins"))
+            .and_then(|item| item.as_array_mut())
+        {
+            disabled.retain(|p| p.as_str().is_none_or(|name| !plugins.contains(&name)));
+            Self::fmt_toml_array(disabled);
+        }
+        self.save()
+    }
+
+    /// Move plugins from tool.nonebot.plugins to tool.nbr.disabled-plugins
+    pub fn disable_plugins(&mut self, plugins: Vec<&str>) -> Result<()> {
+        let plugins_arr_mut = self.plugins_array_mut()?;
+        plugins_arr_mut.retain(|p| p.as_str().is_none_or(|name| !plugins.contains(&name)));
+        Self::fmt_toml_array(plugins_arr_mut);
+
+        let disabled = self.disabled_plugins_array_mut()?;
+        for plugin in plugins {
+            if !disabled.iter().any(|p| p.as_str() == Some(plugin)) {
+                disabled.push(plugin);
+            }
+        }
+        Self::fmt_toml_array(disabled);
+        self.save()
+    }
+
+    /// Move plugins from tool.nbr.disabled-plugins back to tool.nonebot.plugins
+    pub fn enable_plugins(&mut self, plugins: Vec<&str>) -> Result<()> {
+        let disabled = self.disabled_plugins_array_mut()?;
+        disabled.retain(|p| p.as_str().is_none_or(|name| !plugins.contains(&name)));
+        Self::fmt_toml_array(disabled);
+        self.add_plugins(plugins)
+    }
+
+    pub fn add_plugin_dirs(&mut self, dirs: Vec<&str>) -> Result<()> {
+        let dirs_arr_mut = self.plugin_dirs_array_mut()?;
+        for dir in dirs {
+            if !dirs_arr_mut.iter().any(|d| d.as_str() == Some(dir)) {
+                dirs_arr_mut.push(dir);
+            }
+        }
+        Self::fmt_toml_array(dirs_arr_mut);
+        self.save()
+    }
+
+    pub fn remove_plugin_dirs(&mut self, dirs: Vec<&str>) -> Result<()> {
+        let dirs_arr_mut = self.plugin_dirs_array_mut()?;
+        dirs_arr_mut.retain(|d| d.as_str().is_none_or(|dir| !dirs.contains(&dir)));
+        Self::fmt_toml_array(dirs_arr_mut);
         self.save()
     }
 
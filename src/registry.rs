@@ -0,0 +1,357 @@
+//! Registry cache module for nbr
+//!
+//! Registry indexes (`plugins.json`, `adapters.json`) are cached together with
+//! their fetch time, source url and HTTP validators. Once the cache is older than
+//! the TTL it is revalidated with a conditional request, and if the network fails
+//! the stale copy is used instead.
+//!
+//! Indexes can come from several sources (mirrors, private registries, local files),
+//! which are merged in priority order.
+use crate::config::{RegistryConfig, get_cache_dir};
+use crate::dist;
+use crate::utils::terminal_utils;
+use crate::uv::Package;
+use crate::version;
+use anyhow::{Context, Result};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{Client, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+/// Official NoneBot registry
+pub const OFFICIAL_REGISTRY: &str = "https://registry.nonebot.dev";
+
+/// Source name reserved for the official registry
+pub const OFFICIAL_SOURCE: &str = "official";
+
+/// Kind of registry index
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegistryIndex {
+    Plugins,
+    Adapters,
+}
+
+impl fmt::Display for RegistryIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plugins => write!(f, "plugins"),
+            Self::Adapters => write!(f, "adapters"),
+        }
+    }
+}
+
+impl RegistryIndex {
+    /// Cache file name of a source, the official registry keeps `plugins.json` / `adapters.json`
+    fn cache_file_name(&self, source_name: &str) -> String {
+        if source_name == OFFICIAL_SOURCE {
+            format!("{self}.json")
+        } else {
+            let source_name = source_name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect::<String>();
+            format!("{self}.{source_name}.json")
+        }
+    }
+}
+
+/// Resolve (name, url) of every source providing the index, in priority order
+///
+/// Configured sources come first, registry.nonebot.dev last unless disabled.
+/// Sources with a duplicated or the reserved `official` name are ignored.
+pub fn resolve_sources(config: &RegistryConfig, index: RegistryIndex) -> Vec<(String, String)> {
+    let mut names = HashSet::new();
+    let mut sources: Vec<(String, String)> = config
+        .sources
+        .iter()
+        .filter(|source| {
+            if source.name == OFFICIAL_SOURCE {
+                warn!("Registry source name '{OFFICIAL_SOURCE}' is reserved for registry.nonebot.dev, ignoring it");
+                return false;
+            }
+            names.insert(source.name.as_str())
+        })
+        .filter_map(|source| {
+            let url = match index {
+                RegistryIndex::Plugins => source.plugins.as_ref(),
+                RegistryIndex::Adapters => source.adapters.as_ref(),
+            };
+            url.map(|url| (source.name.clone(), url.clone()))
+        })
+        .collect();
+    if config.use_official {
+        sources.push((
+            OFFICIAL_SOURCE.to_string(),
+            format!("{OFFICIAL_REGISTRY}/{index}.json"),
+        ));
+    }
+    sources
+}
+
+/// Fetch an index from every source and merge the entries by `key`
+///
+/// When several sources provide the same key, the source with the higher priority wins.
+/// A failing source is skipped with a warning, unless every source fails.
+pub async fn fetch_index<T, K>(
+    client: &Client,
+    config: &RegistryConfig,
+    index: RegistryIndex,
+    refresh: bool,
+    key: K,
+) -> Result<HashMap<String, T>>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    K: Fn(&T) -> String,
+{
+    let sources = resolve_sources(config, index);
+    if sources.is_empty() {
+        anyhow::bail!("No registry source provides {index}, check your registry config");
+    }
+
+    let cache_dir = get_cache_dir()?;
+    let mut merged = HashMap::new();
+    let mut last_error = None;
+    for (name, url) in &sources {
+        let message = format!("Fetching {index} from {name} registry...");
+        let result = RegistryFetcher::new(client, url, cache_dir.join(index.cache_file_name(name)))
+            .ttl(config.cache_ttl)
+            .refresh(refresh)
+            .message(&message)
+            .fetch::<T>()
+            .await;
+        match result {
+            Ok(items) => {
+                for item in items {
+                    merged.entry(key(&item)).or_insert(item);
+                }
+            }
+            Err(e) => {
+                warn!("Failed to load {index} from registry source '{name}': {e:#}");
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if merged.is_empty() => Err(e),
+        _ => Ok(merged),
+    }
+}
+
+/// Installed packages with a newer registry version, `latest_version` is set to it
+///
+/// `latest_versions` maps normalized package names to the registry versions, so no
+/// request to PyPI is needed.
+pub fn outdated_packages(
+    packages: Vec<Package>,
+    latest_versions: &HashMap<String, String>,
+) -> Vec<Package> {
+    packages
+        .into_iter()
+        .filter_map(|mut package| {
+            let latest = latest_versions.get(&dist::normalize_name(&package.name))?;
+            if !version::is_newer(latest, &package.version) {
+                return None;
+            }
+            package.latest_version = Some(latest.clone());
+            Some(package)
+        })
+        .collect()
+}
+
+/// A cached registry index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry<T> {
+    /// Unix timestamp (seconds) of the last successful fetch or revalidation
+    pub fetched_at: u64,
+    /// Url the data was fetched from
+    pub source: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub data: Vec<T>,
+}
+
+impl<T> CacheEntry<T> {
+    pub fn age(&self) -> u64 {
+        now().saturating_sub(self.fetched_at)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> CacheEntry<T> {
+    /// Read a cache entry, `None` if it is missing or in an outdated format
+    pub fn read(cache_file: &Path) -> Option<Self> {
+        let content = std::fs::read(cache_file).ok()?;
+        serde_json::from_slice(&content)
+            .inspect_err(|e| debug!("Ignoring invalid cache {}: {}", cache_file.display(), e))
+            .ok()
+    }
+
+    pub fn write(&self, cache_file: &Path) -> Result<()> {
+        std::fs::write(cache_file, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write cache {}", cache_file.display()))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Fetch a registry index through the cache
+pub struct RegistryFetcher<'a> {
+    client: &'a Client,
+    url: &'a str,
+    cache_file: PathBuf,
+    ttl: u64,
+    refresh: bool,
+    message: &'a str,
+}
+
+impl<'a> RegistryFetcher<'a> {
+    pub fn new(client: &'a Client, url: &'a str, cache_file: PathBuf) -> Self {
+        Self {
+            client,
+            url,
+            cache_file,
+            ttl: 24 * 60 * 60,
+            refresh: false,
+            message: "Fetching from registry...",
+        }
+    }
+
+    /// Set the cache TTL in seconds
+    pub fn ttl(&mut self, ttl: u64) -> &mut Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Revalidate the cache even if it is still fresh
+    pub fn refresh(&mut self, refresh: bool) -> &mut Self {
+        self.refresh = refresh;
+        self
+    }
+
+    /// Set the spinner message shown while fetching
+    pub fn message(&mut self, message: &'a str) -> &mut Self {
+        self.message = message;
+        self
+    }
+
+    pub async fn fetch<T: Serialize + DeserializeOwned + Clone>(&self) -> Result<Vec<T>> {
+        // 本地文件不走缓存
+        if self.url.starts_with("file://") {
+            debug!("Loading {} from local file", self.url);
+            // Url 会处理 Windows 盘符路径(file:///C:/...)
+            let path = Url::parse(self.url)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .with_context(|| format!("Invalid file url {}", self.url))?;
+            let content =
+                std::fs::read(path).with_context(|| format!("Failed to read {}", self.url))?;
+            return serde_json::from_slice(&content)
+                .with_context(|| format!("Failed to parse {}", self.url));
+        }
+
+        // 缓存来源变更后，旧缓存不可用于条件请求
+        let cached = CacheEntry::<T>::read(&self.cache_file).filter(|e| e.source == self.url);
+
+        if let Some(entry) = cached.as_ref()
+            && !self.refresh
+            && entry.age() < self.ttl
+        {
+            debug!(
+                "Loading {} from cache: {}",
+                self.url,
+                self.cache_file.display()
+            );
+            return Ok(entry.data.clone());
+        }
+
+        let spinner = terminal_utils::create_spinner(self.message);
+        let result = self.revalidate(cached.as_ref()).await;
+        spinner.finish_and_clear();
+
+        match (result, cached) {
+            (Ok(entry), _) => {
+                // 缓存只是优化，写入失败不影响结果
+                if let Err(e) = entry.write(&self.cache_file) {
+                    warn!("{:#}", e);
+                }
+                Ok(entry.data)
+            }
+            (Err(e), Some(stale)) => {
+                warn!(
+                    "Failed to fetch {}, using cached data from {} hour(s) ago: {:#}",
+                    self.url,
+                    stale.age() / 3600,
+                    e
+                );
+                Ok(stale.data)
+            }
+            (Err(e), None) => Err(e),
+        }
+    }
+
+    /// Send a (conditional) request and build the new cache entry
+    async fn revalidate<T: DeserializeOwned + Clone>(
+        &self,
+        cached: Option<&CacheEntry<T>>,
+    ) -> Result<CacheEntry<T>> {
+        let mut request = self.client.get(self.url);
+        if let Some(entry) = cached {
+            if let Some(ref etag) = entry.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(ref last_modified) = entry.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("Network error while fetching {}", self.url))?;
+
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(entry) = cached
+        {
+            debug!("{} not modified, refreshing cache timestamp", self.url);
+            return Ok(CacheEntry {
+                fetched_at: now(),
+                ..entry.clone()
+            });
+        }
+
+        let response = response
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch {}", self.url))?;
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_owned)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let data = response
+            .json::<Vec<T>>()
+            .await
+            .with_context(|| format!("Failed to parse {}", self.url))?;
+
+        Ok(CacheEntry {
+            fetched_at: now(),
+            source: self.url.to_string(),
+            etag,
+            last_modified,
+            data,
+        })
+    }
+}
@@ -0,0 +1,218 @@
+//! Registry search module for nbr
+//!
+//! This module ranks registry entries (plugins, adapters) against a search query.
+//! Matching is case-insensitive and falls back to fuzzy subsequence matching.
+//! Chinese text can also be matched by its pinyin, full or initials.
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// An entry that can be ranked against a search query
+pub trait Searchable {
+    /// Stable identifier, used to break ties between equal scores
+    fn search_id(&self) -> &str;
+
+    /// Text fields to match against, with their weights
+    fn search_fields(&self) -> Vec<(&str, u32)>;
+}
+
+/// Score how well a single query term matches `text`, higher is better
+///
+/// exact > prefix > substring > fuzzy subsequence
+pub fn match_score(term: &str, text: &str) -> Option<u32> {
+    let term = term.to_lowercase();
+    let text = text.to_lowercase();
+    if term.is_empty() || text.is_empty() {
+        return None;
+    }
+    direct_score(&term, &text).or_else(|| pinyin_score(&term, &text))
+}
+
+fn direct_score(term: &str, text: &str) -> Option<u32> {
+    if text == term {
+        Some(100)
+    } else if text.starts_with(term) {
+        Some(80)
+    } else if let Some(pos) = text.find(term) {
+        // 单词边界上的匹配优先
+        let at_boundary = text[..pos]
+            .chars()
+            .last()
+            .is_some_and(|c| !c.is_alphanumeric());
+        Some(if at_boundary { 70 } else { 60 })
+    } else {
+        fuzzy_score(term, text)
+    }
+}
+
+/// Match an ASCII term against the pinyin of Chinese text, `fwq` / `fuwuqi` -> `服务器`
+///
+/// Scored slightly below a direct match of the same kind.
+fn pinyin_score(term: &str, text: &str) -> Option<u32> {
+    if !term.is_ascii() {
+        return None;
+    }
+    let (full, initials) = to_pinyin(text)?;
+    let score = [full, initials]
+        .iter()
+        .filter_map(|pinyin| direct_score(term, pinyin))
+        .max()?;
+    Some(score * 9 / 10)
+}
+
+/// Pinyin lookup table, character -> toneless syllable
+fn pinyin_table() -> &'static HashMap<char, &'static str> {
+    static PINYIN_TABLE: OnceLock<HashMap<char, &'static str>> = OnceLock::new();
+    PINYIN_TABLE.get_or_init(|| {
+        include_str!("data/pinyin.txt")
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once(' '))
+            .flat_map(|(syllable, chars)| chars.chars().map(move |c| (c, syllable)))
+            .collect()
+    })
+}
+
+/// Convert text into (full pinyin, pinyin initials), `None` if there is no Chinese character
+///
+/// Characters without pinyin are kept as-is, whitespace is dropped.
+/// 多音字只取常用读音
+pub fn to_pinyin(text: &str) -> Option<(String, String)> {
+    let table = pinyin_table();
+    let mut full = String::new();
+    let mut initials = String::new();
+    let mut has_han = false;
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if let Some(syllable) = table.get(&c) {
+            has_han = true;
+            full.push_str(syllable);
+            initials.push_str(&syllable[..1]);
+        } else {
+            full.extend(c.to_lowercase());
+            initials.extend(c.to_lowercase());
+        }
+    }
+    has_han.then_some((full, initials))
+}
+
+/// Fuzzy subsequence match, only accepted when the matched span is compact
+fn fuzzy_score(term: &str, text: &str) -> Option<u32> {
+    let term: Vec<char> = term.chars().collect();
+    let mut matched = 0;
+    let mut first = None;
+    let mut last = 0;
+    for (i, c) in text.chars().enumerate() {
+        if matched < term.len() && c == term[matched] {
+            first.get_or_insert(i);
+            last = i;
+            matched += 1;
+        }
+    }
+    if matched < term.len() || term.len() < 2 {
+        return None;
+    }
+    let span = last - first? + 1;
+    // 匹配跨度超过查询长度两倍时视为噪音
+    if span > term.len() * 2 {
+        return None;
+    }
+    Some((40 * term.len() / span) as u32)
+}
+
+/// Score an entry against a (possibly multi-word) query
+///
+/// Every whitespace separated term has to match at least one field.
+pub fn score<T: Searchable>(item: &T, query: &str) -> Option<u32> {
+    let fields = item.search_fields();
+    let mut total = 0;
+    for term in query.split_whitespace() {
+        total += fields
+            .iter()
+            .filter_map(|(text, weight)| match_score(term, text).map(|s| s * weight))
+            .max()?;
+    }
+    (total > 0).then_some(total)
+}
+
+/// Rank entries against a query, best matches first
+pub fn rank<'a, T: Searchable>(
+    items: impl IntoIterator<Item = &'a T>,
+    query: &str,
+) -> Vec<(u32, &'a T)> {
+    let mut ranked: Vec<(u32, &T)> = items
+        .into_iter()
+        .filter_map(|item| score(item, query).map(|s| (s, item)))
+        .collect();
+    ranked.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.search_id().cmp(b.search_id()))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Entry(&'static str, &'static str);
+
+    impl Searchable for Entry {
+        fn search_id(&self) -> &str {
+            self.0
+        }
+
+        fn search_fields(&self) -> Vec<(&str, u32)> {
+            vec![(self.0, 3), (self.1, 1)]
+        }
+    }
+
+    #[test]
+    fn test_match_score_order() {
+        let exact = match_score("status", "status").unwrap();
+        let prefix = match_score("STATUS", "status-viewer").unwrap();
+        let boundary = match_score("status", "nonebot-plugin-status").unwrap();
+        let substring = match_score("tatu", "nonebot-plugin-status").unwrap();
+        let fuzzy = match_score("stts", "status").unwrap();
+        assert!(exact > prefix);
+        assert!(prefix > boundary);
+        assert!(boundary > substring);
+        assert!(substring > fuzzy);
+        assert!(match_score("xyz", "status").is_none());
+        // 过于分散的子序列不算匹配
+        assert!(match_score("np", "nonebot-plugin-status").is_none());
+    }
+
+    #[test]
+    fn test_pinyin_match() {
+        let (full, initials) = to_pinyin("服务器状态查看").unwrap();
+        assert_eq!(full, "fuwuqizhuangtaichakan");
+        assert_eq!(initials, "fwqztck");
+        assert!(to_pinyin("nonebot").is_none());
+
+        assert!(match_score("fwq", "服务器状态查看").is_some());
+        assert!(match_score("ZhuangTai", "服务器状态查看").is_some());
+        assert!(match_score("ztck", "服务器状态查看").is_some());
+        assert!(match_score("xyz", "服务器状态查看").is_none());
+        // 直接匹配优先于拼音匹配
+        assert!(match_score("状态", "服务器状态查看") > match_score("zt", "服务器状态查看"));
+    }
+
+    #[test]
+    fn test_rank() {
+        let entries = [
+            Entry("nonebot-plugin-weather", "查询天气"),
+            Entry("nonebot-plugin-status", "server status"),
+            Entry("nonebot-plugin-abs", "nothing"),
+        ];
+        let ranked = rank(&entries, "Status");
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(ranked[0].1.0, "nonebot-plugin-status");
+
+        let ranked = rank(&entries, "nonebot plugin");
+        assert_eq!(ranked.len(), 3);
+        // 分数相同时按 id 排序
+        assert_eq!(ranked[0].1.0, "nonebot-plugin-abs");
+
+        assert!(rank(&entries, "weather status").is_empty());
+    }
+}
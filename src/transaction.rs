@@ -0,0 +1,190 @@
+//! Project transaction module for nbr
+//!
+//! Commands changing a project run `uv` and edit `pyproject.toml` in several steps. A
+//! transaction snapshots the project files first and restores them if any step fails, so a
+//! failed command never leaves the project half-modified. When `uv.lock` is restored the
+//! virtual environment is synced back to it as well.
+use crate::uv;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{error, warn};
+
+/// Files restored on rollback
+pub const PROJECT_FILES: [&str; 2] = ["pyproject.toml", "uv.lock"];
+
+pub struct ProjectTransaction {
+    work_dir: PathBuf,
+    /// Description of the operation, e.g. `plugin install`
+    operation: String,
+    /// Snapshot of each project file, `None` if it did not exist
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl ProjectTransaction {
+    pub fn begin(work_dir: &Path, operation: impl Into<String>) -> Result<Self> {
+        let files = PROJECT_FILES
+            .iter()
+            .map(|name| {
+                let path = work_dir.join(name);
+                let content = if path.exists() {
+                    let content = fs::read(&path)
+                        .with_context(|| format!("Failed to snapshot {}", path.display()))?;
+                    Some(content)
+                } else {
+                    None
+                };
+                Ok((path, content))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            work_dir: work_dir.to_path_buf(),
+            operation: operation.into(),
+            files,
+        })
+    }
+
+    /// Restore the changed files, returns their names
+    pub fn rollback(&self) -> Result<Vec<String>> {
+        let mut restored = Vec::new();
+        for (path, snapshot) in &self.files {
+            let current = fs::read(path).ok();
+            if current == *snapshot {
+                continue;
+            }
+            match snapshot {
+                Some(content) => fs::write(path, content),
+                // 操作过程中新建的文件直接删除
+                None => fs::remove_file(path),
+            }
+            .with_context(|| format!("Failed to restore {}", path.display()))?;
+            restored.push(
+                path.file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        Ok(restored)
+    }
+
+    /// Sync the virtual environment to the restored `uv.lock`
+    ///
+    /// Skipped without a `.venv`, there is nothing installed to revert then.
+    fn sync_environment(&self) -> Result<()> {
+        if !self.work_dir.join(".venv").is_dir() {
+            return Ok(());
+        }
+        uv::sync(None).working_dir(&self.work_dir).run()
+    }
+
+    /// Pass `result` through, rolling back first if it is an error
+    pub fn finish<T>(self, result: Result<T>) -> Result<T> {
+        if result.is_ok() {
+            return result;
+        }
+        let restored = match self.rollback() {
+            Ok(restored) => restored,
+            Err(e) => {
+                error!(
+                    "{} failed and the rollback failed too, check {} manually: {:#}",
+                    self.operation,
+                    PROJECT_FILES.join(", "),
+                    e
+                );
+                return result;
+            }
+        };
+        if restored.is_empty() {
+            return result;
+        }
+        // 还原 uv.lock 后虚拟环境仍是失败时的状态，需要重新同步
+        if !restored.iter().any(|name| name == "uv.lock") {
+            warn!(
+                "{} failed, rolled back {}",
+                self.operation,
+                restored.join(", ")
+            );
+        } else if let Err(e) = self.sync_environment() {
+            warn!(
+                "{} failed, rolled back {} but failed to sync the virtual environment, run `uv sync` manually: {:#}",
+                self.operation,
+                restored.join(", "),
+                e
+            );
+        } else {
+            warn!(
+                "{} failed, rolled back {} and synced the virtual environment",
+                self.operation,
+                restored.join(", ")
+            );
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pyproject::NbTomlEditor;
+
+    #[test]
+    fn test_rollback_on_failure() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pyproject = temp_dir.path().join("pyproject.toml");
+        let lock = temp_dir.path().join("uv.lock");
+        fs::write(&pyproject, "[project]\nname = \"bot\"\n").unwrap();
+
+        let transaction = ProjectTransaction::begin(temp_dir.path(), "plugin install").unwrap();
+        fs::write(&pyproject, "[project]\nname = \"half-modified\"\n").unwrap();
+        fs::write(&lock, "version = 1\n").unwrap();
+        let result: Result<()> = transaction.finish(Err(anyhow::anyhow!("uv add failed")));
+        assert_eq!(result.unwrap_err().to_string(), "uv add failed");
+        assert_eq!(
+            fs::read_to_string(&pyproject).unwrap(),
+            "[project]\nname = \"bot\"\n"
+        );
+        assert!(!lock.exists());
+
+        let transaction = ProjectTransaction::begin(temp_dir.path(), "plugin install").unwrap();
+        fs::write(&lock, "version = 1\n").unwrap();
+        assert_eq!(transaction.rollback().unwrap(), ["uv.lock"]);
+        fs::write(&lock, "version = 1\n").unwrap();
+        transaction.finish(Ok(())).unwrap();
+        assert!(lock.exists());
+    }
+
+    #[test]
+    fn test_rollback_wrapped_operation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let work_dir = temp_dir.path();
+        let pyproject = "[project]\nname = \"bot\"\ndependencies = []\n\n\
+            [tool.nonebot]\nadapters = []\nplugins = []\nplugin_dirs = []\nbuiltin_plugins = []\n";
+        fs::write(work_dir.join("pyproject.toml"), pyproject).unwrap();
+        fs::write(work_dir.join("uv.lock"), "version = 1\n").unwrap();
+
+        // 模拟 uv add 已修改项目文件，之后的步骤失败
+        let operation = || -> Result<()> {
+            fs::write(
+                work_dir.join("uv.lock"),
+                "version = 1\n\n[[package]]\nname = \"nonebot-plugin-status\"\n",
+            )?;
+            NbTomlEditor::with_work_dir(Some(work_dir))?
+                .add_plugins(vec!["nonebot_plugin_status"])?;
+            anyhow::bail!("Failed to read the plugin metadata")
+        };
+        let transaction = ProjectTransaction::begin(work_dir, "plugin install").unwrap();
+        let result = transaction.finish(operation());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Failed to read the plugin metadata"
+        );
+        assert_eq!(
+            fs::read_to_string(work_dir.join("pyproject.toml")).unwrap(),
+            pyproject
+        );
+        assert_eq!(
+            fs::read_to_string(work_dir.join("uv.lock")).unwrap(),
+            "version = 1\n"
+        );
+    }
+}
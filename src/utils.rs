@@ -22,6 +22,19 @@ pub mod process_utils {
         args: &[&str],
         working_dir: Option<&Path>,
         timeout_secs: u64,
+    ) -> Result<Output> {
+        execute_command_with_envs(program, args, &[], working_dir, timeout_secs).await
+    }
+
+    /// Execute a command with extra environment variables, timeout and capture output
+    ///
+    /// The variables are only set on the child process and never logged.
+    pub async fn execute_command_with_envs(
+        program: &str,
+        args: &[&str],
+        envs: &[(&str, &str)],
+        working_dir: Option<&Path>,
+        timeout_secs: u64,
     ) -> Result<Output> {
         debug!("Executing command: {} {}", program, args.join(" "));
 
@@ -30,7 +43,10 @@ pub mod process_utils {
         }
 
         let mut cmd = Command::new(program);
-        cmd.args(args).stdout(Stdio::piped()).stderr(Stdio::piped());
+        cmd.args(args)
+            .envs(envs.iter().copied())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
 
         if let Some(dir) = working_dir {
             cmd.current_dir(dir);
@@ -193,6 +209,44 @@ pub mod net_utils {
     }
 }
 
+/// File system utilities
+pub mod fs_utils {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Temporary directory removed on drop
+    pub struct TempDir(PathBuf);
+
+    impl TempDir {
+        pub fn new(prefix: &str) -> Result<Self> {
+            let nanos = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default();
+            let dir = std::env::temp_dir().join(format!(
+                "nbr-{}-{}-{}",
+                prefix,
+                std::process::id(),
+                nanos
+            ));
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
+            Ok(Self(dir))
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+}
+
 /// Terminal utilities
 pub mod terminal_utils {
     use super::*;
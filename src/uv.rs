@@ -1,8 +1,10 @@
 use crate::log::StyledText;
 use crate::utils::{process_utils, terminal_utils};
+use crate::version::Version;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::{
+    fs,
     hash::{Hash, Hasher},
     path::Path,
 };
@@ -48,6 +50,27 @@ pub fn upgrade(packages: Vec<&str>) -> Result<()> {
     sync(None).run()
 }
 
+/// Lock an upgrade without applying it, returns `uv.lock` before and after the upgrade
+pub async fn preview_upgrade(packages: &[&str], work_dir: &Path) -> Result<(String, String)> {
+    let lock_file = work_dir.join("uv.lock");
+    let before = fs::read_to_string(&lock_file).context("Failed to read uv.lock")?;
+    let mut args = vec!["lock"];
+    args.extend(packages.iter().flat_map(|p| ["-P", *p]));
+    let result = CmdBuilder::uv(args)
+        .working_dir(work_dir)
+        .timeout(300)
+        .run_async_with_spinner("Resolving the upgrade...")
+        .await;
+    let after = fs::read_to_string(&lock_file);
+    // 预览不修改项目，还原 uv.lock
+    fs::write(&lock_file, &before).context("Failed to restore uv.lock")?;
+    result?;
+    Ok((
+        before,
+        after.context("Failed to read the upgraded uv.lock")?,
+    ))
+}
+
 pub async fn is_installed(package: &str) -> bool {
     show(package).run_async().await.is_ok()
 }
@@ -161,10 +184,15 @@ impl Hash for Package {
 
 impl Package {
     pub fn is_outdated(&self) -> bool {
-        if let Some(latest_version) = self.latest_version.as_ref() {
-            &self.version != latest_version
-        } else {
-            false
+        let Some(latest_version) = self.latest_version.as_ref() else {
+            return false;
+        };
+        match (
+            Version::parse(latest_version),
+            Version::parse(&self.version),
+        ) {
+            (Ok(latest), Ok(installed)) => latest > installed,
+            _ => &self.version != latest_version,
         }
     }
 
@@ -194,6 +222,7 @@ pub struct CmdBuilder<'a> {
     pub args: Vec<&'a str>,
     pub working_dir: Option<&'a Path>,
     pub timeout_secs: u16,
+    pub envs: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> CmdBuilder<'a> {
@@ -204,6 +233,7 @@ impl<'a> CmdBuilder<'a> {
             args,
             working_dir: None,
             timeout_secs: 5,
+            envs: Vec::new(),
         }
     }
 
@@ -240,15 +270,22 @@ impl<'a> CmdBuilder<'a> {
         self
     }
 
+    /// Set an environment variable of the command only, e.g. credentials
+    pub fn env(&mut self, key: &'a str, value: &'a str) -> &mut Self {
+        self.envs.push((key, value));
+        self
+    }
+
     pub fn run(&self) -> Result<()> {
         process_utils::execute_interactive(self.cmd, &self.args, self.working_dir)
     }
 
     /// Run the command asynchronously and return the stdout as a string
     pub async fn run_async(&self) -> Result<String> {
-        let output = process_utils::execute_command_with_output(
+        let output = process_utils::execute_command_with_envs(
             self.cmd,
             &self.args,
+            &self.envs,
             self.working_dir,
             self.timeout_secs as u64,
         )
@@ -271,8 +308,9 @@ pub struct AddBuilder<'a> {
     pub upgrade: bool,
     pub index_url: Option<&'a str>,
     pub working_dir: Option<&'a Path>,
-    pub extras: Option<Vec<&'a str>>,
     pub reinstall: bool,
+    pub editable: bool,
+    pub frozen: bool,
 }
 
 impl<'a> AddBuilder<'a> {
@@ -282,8 +320,9 @@ impl<'a> AddBuilder<'a> {
             upgrade: false,
             index_url: None,
             working_dir: None,
-            extras: None,
             reinstall: false,
+            editable: false,
+            frozen: false,
         }
     }
 
@@ -307,17 +346,25 @@ impl<'a> AddBuilder<'a> {
         self
     }
 
-    pub fn extras(&mut self, extras: Vec<&'a str>) -> &mut Self {
-        self.extras = Some(extras);
+    pub fn reinstall(&mut self, reinstall: bool) -> &mut Self {
+        self.reinstall = reinstall;
         self
     }
 
-    pub fn reinstall(&mut self, reinstall: bool) -> &mut Self {
-        self.reinstall = reinstall;
+    /// Add local paths in editable mode
+    pub fn editable(&mut self, editable: bool) -> &mut Self {
+        self.editable = editable;
         self
     }
 
-    pub fn run(&self) -> Result<()> {
+    /// Only edit pyproject.toml, without locking and syncing
+    pub fn frozen(&mut self, frozen: bool) -> &mut Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Arguments passed to uv, packages are full requirements like `name[extra]>=1.0`
+    pub fn args(&self) -> Vec<&'a str> {
         let mut args: Vec<&str> = vec!["add"];
         args.extend(self.packages.clone());
         if self.upgrade {
@@ -327,13 +374,19 @@ impl<'a> AddBuilder<'a> {
             args.push("--index-url");
             args.push(index_url);
         }
-        if let Some(ref extras) = self.extras {
-            let extras = extras.iter().flat_map(|e| ["--extra", e]);
-            args.extend(extras);
-        }
         if self.reinstall {
             args.push("--reinstall");
         }
-        process_utils::execute_interactive("uv", &args, self.working_dir)
+        if self.editable {
+            args.push("--editable");
+        }
+        if self.frozen {
+            args.push("--frozen");
+        }
+        args
+    }
+
+    pub fn run(&self) -> Result<()> {
+        process_utils::execute_interactive("uv", &self.args(), self.working_dir)
     }
 }
@@ -0,0 +1,230 @@
+//! Version module for nbr
+//!
+//! Parses and orders package versions following PEP 440, local version labels are ignored.
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+const VERSION_PATTERN: &str = r"(?ix)^\s*v?
+    (?:(?P<epoch>[0-9]+)!)?
+    (?P<release>[0-9]+(?:\.[0-9]+)*)
+    (?:[-_.]?(?P<pre_l>alpha|beta|preview|pre|rc|a|b|c)[-_.]?(?P<pre_n>[0-9]+)?)?
+    (?:-(?P<post_n1>[0-9]+)|[-_.]?(?P<post_l>post|rev|r)[-_.]?(?P<post_n2>[0-9]+)?)?
+    (?:[-_.]?(?P<dev_l>dev)[-_.]?(?P<dev_n>[0-9]+)?)?
+    (?:\+[a-z0-9]+(?:[-_.][a-z0-9]+)*)?
+    \s*$";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PreRelease {
+    Alpha,
+    Beta,
+    Rc,
+}
+
+#[derive(Debug, Clone)]
+pub struct Version {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(PreRelease, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+}
+
+impl Version {
+    pub fn parse(version: &str) -> Result<Self> {
+        let re = Regex::new(VERSION_PATTERN).context("Invalid regex pattern")?;
+        let captures = re
+            .captures(version)
+            .with_context(|| format!("Invalid version: {}", version))?;
+        let number = |name: &str| {
+            captures
+                .name(name)
+                .map(|m| m.as_str().parse::<u64>())
+                .transpose()
+                .with_context(|| format!("Invalid version: {}", version))
+        };
+
+        let release = captures["release"]
+            .split('.')
+            .map(str::parse)
+            .collect::<Result<Vec<u64>, _>>()
+            .with_context(|| format!("Invalid version: {}", version))?;
+        let pre = match captures.name("pre_l") {
+            Some(label) => {
+                let kind = match label.as_str().to_lowercase().as_str() {
+                    "a" | "alpha" => PreRelease::Alpha,
+                    "b" | "beta" => PreRelease::Beta,
+                    _ => PreRelease::Rc,
+                };
+                Some((kind, number("pre_n")?.unwrap_or(0)))
+            }
+            None => None,
+        };
+        let post = match number("post_n1")? {
+            Some(n) => Some(n),
+            None if captures.name("post_l").is_some() => Some(number("post_n2")?.unwrap_or(0)),
+            None => None,
+        };
+        let dev = match captures.name("dev_l") {
+            Some(_) => Some(number("dev_n")?.unwrap_or(0)),
+            None => None,
+        };
+
+        Ok(Self {
+            epoch: number("epoch")?.unwrap_or(0),
+            release,
+            pre,
+            post,
+            dev,
+        })
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        self.pre.is_some() || self.dev.is_some()
+    }
+
+    /// Versions with the same key are expected to be compatible, `0.x` counts the minor version
+    fn compatibility_key(&self) -> (u64, u64, u64) {
+        let major = self.release.first().copied().unwrap_or(0);
+        let minor = match major {
+            0 => self.release.get(1).copied().unwrap_or(0),
+            _ => 0,
+        };
+        (self.epoch, major, minor)
+    }
+
+    /// Sort key, `1.0.dev0 < 1.0a1 < 1.0 < 1.0.post1`
+    fn key(&self) -> (u64, &[u64], [(u8, u64); 3]) {
+        // 1.0 与 1.0.0 相等
+        let end = self
+            .release
+            .iter()
+            .rposition(|n| *n != 0)
+            .map_or(0, |i| i + 1);
+        let pre = match (self.pre, self.post, self.dev) {
+            (Some((kind, n)), _, _) => (kind as u8 + 1, n),
+            // 仅有 dev 的版本排在所有预发布版本之前
+            (None, None, Some(_)) => (0, 0),
+            (None, _, _) => (u8::MAX, 0),
+        };
+        let post = self.post.map_or((0, 0), |n| (1, n));
+        let dev = self.dev.map_or((1, 0), |n| (0, n));
+        (self.epoch, &self.release[..end], [pre, post, dev])
+    }
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch > 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        let release: Vec<String> = self.release.iter().map(u64::to_string).collect();
+        write!(f, "{}", release.join("."))?;
+        if let Some((kind, n)) = self.pre {
+            let label = match kind {
+                PreRelease::Alpha => "a",
+                PreRelease::Beta => "b",
+                PreRelease::Rc => "rc",
+            };
+            write!(f, "{label}{n}")?;
+        }
+        if let Some(n) = self.post {
+            write!(f, ".post{n}")?;
+        }
+        if let Some(n) = self.dev {
+            write!(f, ".dev{n}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `latest` is newer than `installed`, `false` if either is not a valid version
+pub fn is_newer(latest: &str, installed: &str) -> bool {
+    match (Version::parse(latest), Version::parse(installed)) {
+        (Ok(latest), Ok(installed)) => latest > installed,
+        _ => false,
+    }
+}
+
+/// Whether updating from `installed` to `latest` crosses a major version
+pub fn crosses_major(installed: &str, latest: &str) -> bool {
+    match (Version::parse(installed), Version::parse(latest)) {
+        (Ok(installed), Ok(latest)) => installed.compatibility_key() != latest.compatibility_key(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ordering() {
+        let ordered = [
+            "0.9",
+            "1.0.dev0",
+            "1.0a1",
+            "1.0-alpha.2",
+            "1.0b2.dev3",
+            "1.0b2",
+            "1.0rc1",
+            "1.0",
+            "1.0.post1.dev0",
+            "1.0-1",
+            "1.0.1",
+            "1.10",
+            "1!0.1",
+        ];
+        let versions: Vec<Version> = ordered.iter().map(|v| v.parse().unwrap()).collect();
+        for pair in versions.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+
+        assert_eq!(
+            Version::parse("v1.0.0+local.1").unwrap(),
+            "1.0".parse().unwrap()
+        );
+        assert_eq!(Version::parse("1.0-ALPHA.2").unwrap().to_string(), "1.0a2");
+        assert!(Version::parse("1.0rc1").unwrap().is_prerelease());
+        assert!(Version::parse("latest").is_err());
+
+        assert!(is_newer("0.10.0", "0.9.5"));
+        assert!(!is_newer("1.0.0", "1.0"));
+        assert!(!is_newer("unknown", "1.0"));
+
+        assert!(crosses_major("1.4.2", "2.0.0rc1"));
+        assert!(crosses_major("0.9.5", "0.10.0"));
+        assert!(!crosses_major("2.3.1", "2.4.0"));
+        assert!(!crosses_major("0.9.0", "0.9.5.post1"));
+    }
+}
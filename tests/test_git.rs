@@ -0,0 +1,81 @@
+use nbr::git::GitSource;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn git(dir: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["-c", "user.name=nbr", "-c", "user.email=nbr@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+fn commit_pyproject(work: &Path, dir: &str, name: &str) -> String {
+    let package_dir = work.join(dir);
+    fs::create_dir_all(&package_dir).unwrap();
+    fs::write(
+        package_dir.join("pyproject.toml"),
+        format!("[project]\nname = \"{name}\"\nversion = \"0.1.0\"\n"),
+    )
+    .unwrap();
+    git(work, &["add", "-A"]);
+    git(work, &["commit", "-q", "-m", name]);
+    git(work, &["rev-parse", "HEAD"])
+}
+
+#[tokio::test]
+async fn test_fetch_package_name_from_local_repo() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let work = temp_dir.path().join("work");
+    let bare = temp_dir.path().join("weather.git");
+    fs::create_dir_all(&work).unwrap();
+    git(&work, &["init", "-q", "-b", "main"]);
+
+    // 仓库名与包名不同，包在子目录中
+    let first = commit_pyproject(&work, "plugin", "nonebot-plugin-weather");
+    git(&work, &["tag", "v0.1.0"]);
+    commit_pyproject(&work, "plugin", "nonebot-plugin-weather-next");
+    git(&work, &["checkout", "-q", "-b", "dev"]);
+    commit_pyproject(&work, "plugin", "nonebot-plugin-weather-dev");
+    git(
+        temp_dir.path(),
+        &[
+            "clone",
+            "-q",
+            "--bare",
+            work.to_str().unwrap(),
+            bare.to_str().unwrap(),
+        ],
+    );
+
+    let url = format!("git+file://{}", bare.display());
+    let fetch = |reference: &str| {
+        let input = format!("{url}{reference}#subdirectory=plugin");
+        async move {
+            GitSource::parse(&input)
+                .unwrap()
+                .fetch_package_name()
+                .await
+                .unwrap()
+        }
+    };
+    assert_eq!(fetch("@main").await, "nonebot-plugin-weather-next");
+    assert_eq!(fetch("@dev").await, "nonebot-plugin-weather-dev");
+    assert_eq!(fetch("@v0.1.0").await, "nonebot-plugin-weather");
+    assert_eq!(fetch(&format!("@{first}")).await, "nonebot-plugin-weather");
+    assert_eq!(
+        fetch(&format!("@{}", &first[..7])).await,
+        "nonebot-plugin-weather"
+    );
+
+    let source = GitSource::parse(&format!("{url}@missing")).unwrap();
+    assert!(source.fetch_package_name().await.is_err());
+    // 仓库根目录没有 pyproject.toml
+    let source = GitSource::parse(&format!("{url}@main")).unwrap();
+    assert!(source.fetch_package_name().await.is_err());
+}
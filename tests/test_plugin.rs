@@ -1,5 +1,8 @@
 mod common;
-use nbr::cli::plugin::PluginManager;
+use nbr::cli::plugin::{
+    PluginLayout, PluginManager, PluginOptions, SearchFilter, create_plugin, find_local_plugins,
+};
+use std::fs;
 
 #[tokio::test]
 async fn test_plugin_list() {
@@ -28,6 +31,84 @@ async fn test_plugin_list() {
     // If we don't have a venv, `uv pip list` might fail or return system packages if not isolated.
     // For this test, let's try to verify the manager can be created and maybe run a search which doesn't require venv.
 
-    let results = manager.search_plugins("echo", 1, false).await;
+    let results = manager
+        .search_plugins("echo", 1, &SearchFilter::default(), false)
+        .await;
     assert!(results.is_ok());
 }
+
+#[tokio::test]
+async fn test_create_plugin() {
+    let (_dir, project_path) = common::create_temp_project(false).await;
+
+    let options = PluginOptions {
+        name: "Hello World".to_string(),
+        module_name: "hello_world".to_string(),
+        layout: PluginLayout::Package,
+        plugin_dir: "src/plugins".to_string(),
+        with_test: true,
+    };
+    create_plugin(&project_path, &options).unwrap();
+
+    let package_dir = project_path.join("src/plugins/hello_world");
+    let init_content = fs::read_to_string(package_dir.join("__init__.py")).unwrap();
+    assert!(init_content.contains(r#"name="Hello World""#));
+    assert!(init_content.contains("config=Config"));
+    assert!(package_dir.join("config.py").exists());
+
+    let test_content =
+        fs::read_to_string(project_path.join("tests/plugins/test_hello_world.py")).unwrap();
+    assert!(test_content.contains(r#"Path("src/plugins/hello_world")"#));
+
+    // Creating the same plugin again should fail
+    assert!(create_plugin(&project_path, &options).is_err());
+
+    // 插件名按 Python 字符串转义
+    let options = PluginOptions {
+        name: r#"Say "hi" \o/"#.to_string(),
+        module_name: "say_hi".to_string(),
+        layout: PluginLayout::File,
+        plugin_dir: "src/plugins".to_string(),
+        with_test: false,
+    };
+    create_plugin(&project_path, &options).unwrap();
+    let content = fs::read_to_string(project_path.join("src/plugins/say_hi.py")).unwrap();
+    assert!(content.contains(r#"name="Say \"hi\" \\o/","#));
+}
+
+#[tokio::test]
+async fn test_find_local_plugins() {
+    let (_dir, project_path) = common::create_temp_project(false).await;
+    let mut options = PluginOptions {
+        name: "Hello World".to_string(),
+        module_name: "hello_world".to_string(),
+        layout: PluginLayout::Package,
+        plugin_dir: "src/plugins".to_string(),
+        with_test: false,
+    };
+    create_plugin(&project_path, &options).unwrap();
+    options.name = "Weather".to_string();
+    options.module_name = "weather".to_string();
+    options.layout = PluginLayout::File;
+    create_plugin(&project_path, &options).unwrap();
+    // 以 _ 开头的模块不会被加载
+    fs::write(project_path.join("src/plugins/_utils.py"), "").unwrap();
+
+    let plugin_dirs = ["src/plugins".to_string(), "missing".to_string()];
+    let plugins = find_local_plugins(&project_path, &plugin_dirs);
+    let found: Vec<(&str, Option<&str>)> = plugins
+        .iter()
+        .map(|p| (p.module_name.as_str(), p.name.as_deref()))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("hello_world", Some("Hello World")),
+            ("weather", Some("Weather"))
+        ]
+    );
+    assert_eq!(
+        plugins[1].path,
+        std::path::Path::new("src/plugins").join("weather.py")
+    );
+}
@@ -0,0 +1,124 @@
+use nbr::cli::plugin::{PublishOptions, publish_plugin};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Minimal stand-in of a pypiserver upload endpoint, returns the url and the uploaded requests
+fn start_index() -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let uploads = Arc::new(Mutex::new(Vec::new()));
+    let received = uploads.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            let mut chunked = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+                    break;
+                }
+                let lower = line.to_lowercase();
+                if let Some(value) = lower.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+                if lower.starts_with("transfer-encoding:") && lower.contains("chunked") {
+                    chunked = true;
+                }
+            }
+            let mut body = Vec::new();
+            if chunked {
+                loop {
+                    let mut size = String::new();
+                    reader.read_line(&mut size).unwrap();
+                    let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+                    let mut chunk = vec![0; size + 2];
+                    reader.read_exact(&mut chunk).unwrap();
+                    if size == 0 {
+                        break;
+                    }
+                    body.extend_from_slice(&chunk[..size]);
+                }
+            } else {
+                body.resize(content_length, 0);
+                reader.read_exact(&mut body).unwrap();
+            }
+            received
+                .lock()
+                .unwrap()
+                .push(String::from_utf8_lossy(&body).to_string());
+            let _ = reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        }
+    });
+    (url, uploads)
+}
+
+#[tokio::test]
+#[ignore = "requires uv, run with `cargo test -- --ignored`"]
+async fn test_publish_upload_to_local_index() {
+    assert!(
+        Command::new("uv").arg("--version").output().is_ok(),
+        "uv is not installed"
+    );
+    let (url, uploads) = start_index();
+
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("pyproject.toml"),
+        r#"[project]
+name = "nonebot-plugin-weather"
+version = "0.1.0"
+description = "Weather forecast"
+requires-python = ">=3.10"
+dependencies = []
+
+[project.urls]
+Repository = "https://github.com/owner/nonebot-plugin-weather"
+
+[build-system]
+requires = ["hatchling"]
+build-backend = "hatchling.build"
+"#,
+    )
+    .unwrap();
+    let module_dir = dir.path().join("src/nonebot_plugin_weather");
+    fs::create_dir_all(&module_dir).unwrap();
+    fs::write(
+        module_dir.join("__init__.py"),
+        r#"__plugin_meta__ = PluginMetadata(name="天气", type="application", supported_adapters=None)"#,
+    )
+    .unwrap();
+
+    // 本地测试索引不校验凭据
+    let options = PublishOptions {
+        upload: true,
+        publish_url: Some(&url),
+        username: Some("nbr"),
+        password: Some("nbr"),
+        skip_check: true,
+        ..Default::default()
+    };
+    publish_plugin(dir.path(), options).await.unwrap();
+
+    let uploads = uploads.lock().unwrap();
+    let files = [
+        "nonebot_plugin_weather-0.1.0-py3-none-any.whl",
+        "nonebot_plugin_weather-0.1.0.tar.gz",
+    ];
+    for file in files {
+        assert!(
+            uploads
+                .iter()
+                .any(|body| body.contains(&format!("filename=\"{file}\""))),
+            "{file} was not uploaded"
+        );
+    }
+    let payload = fs::read_to_string(dir.path().join("dist/registry.json")).unwrap();
+    assert!(payload.contains(r#""project_link": "nonebot-plugin-weather""#));
+}
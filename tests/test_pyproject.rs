@@ -1,4 +1,5 @@
 mod common;
+use nbr::config::ProjectConfig;
 use nbr::pyproject::{Adapter, NbTomlEditor, PyProjectConfig};
 
 #[tokio::test]
@@ -61,3 +62,60 @@ async fn test_remove_plugins() {
     let pyproject_plugins = pyproject.nonebot().unwrap().plugins.as_ref().unwrap();
     assert_eq!(pyproject_plugins.len(), 0);
 }
+
+#[tokio::test]
+async fn test_disable_and_enable_plugins() {
+    let (_dir, project_path) = common::create_temp_project(false).await;
+    let mut editor = NbTomlEditor::with_work_dir(Some(&project_path)).unwrap();
+    editor
+        .add_plugins(vec!["nonebot_plugin_status", "nonebot_plugin_waiter"])
+        .unwrap();
+
+    let mut editor = NbTomlEditor::with_work_dir(Some(&project_path)).unwrap();
+    editor
+        .disable_plugins(vec!["nonebot_plugin_status"])
+        .unwrap();
+    let pyproject = PyProjectConfig::parse(Some(&project_path)).unwrap();
+    let plugins = pyproject.nonebot().unwrap().plugins.clone().unwrap();
+    assert_eq!(plugins, ["nonebot_plugin_waiter"]);
+    let project = ProjectConfig::parse(&project_path).unwrap().unwrap();
+    assert_eq!(project.disabled_plugins, ["nonebot_plugin_status"]);
+
+    let mut editor = NbTomlEditor::with_work_dir(Some(&project_path)).unwrap();
+    editor
+        .enable_plugins(vec!["nonebot_plugin_status"])
+        .unwrap();
+    let pyproject = PyProjectConfig::parse(Some(&project_path)).unwrap();
+    let plugins = pyproject.nonebot().unwrap().plugins.clone().unwrap();
+    assert!(plugins.contains(&"nonebot_plugin_status".to_string()));
+    let project = ProjectConfig::parse(&project_path).unwrap().unwrap();
+    assert!(project.disabled_plugins.is_empty());
+
+    // 卸载禁用的插件时同时移除禁用记录
+    let mut editor = NbTomlEditor::with_work_dir(Some(&project_path)).unwrap();
+    editor
+        .disable_plugins(vec!["nonebot_plugin_waiter"])
+        .unwrap();
+    editor
+        .remove_plugins(vec!["nonebot_plugin_waiter"])
+        .unwrap();
+    let project = ProjectConfig::parse(&project_path).unwrap().unwrap();
+    assert!(project.disabled_plugins.is_empty());
+}
+
+#[tokio::test]
+async fn test_plugin_dirs() {
+    let (_dir, project_path) = common::create_temp_project(false).await;
+    let mut editor = NbTomlEditor::with_work_dir(Some(&project_path)).unwrap();
+    editor
+        .add_plugin_dirs(vec!["src/plugins", "src/extra"])
+        .unwrap();
+    let pyproject = PyProjectConfig::parse(Some(&project_path)).unwrap();
+    let dirs = pyproject.nonebot().unwrap().plugin_dirs.clone().unwrap();
+    assert_eq!(dirs, ["src/plugins", "src/extra"]);
+
+    editor.remove_plugin_dirs(vec!["src/plugins"]).unwrap();
+    let pyproject = PyProjectConfig::parse(Some(&project_path)).unwrap();
+    let dirs = pyproject.nonebot().unwrap().plugin_dirs.clone().unwrap();
+    assert_eq!(dirs, ["src/extra"]);
+}
@@ -0,0 +1,352 @@
+use nbr::config::{NbrConfig, ProjectConfig, RegistryConfig, RegistrySource};
+use nbr::registry::{self, CacheEntry, RegistryFetcher, RegistryIndex};
+use nbr::uv::Package;
+use reqwest::{Client, Url};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A minimal registry stand-in, serves a json array with an ETag and answers 304 on a match
+struct RegistryServer {
+    url: String,
+    requests: Arc<AtomicUsize>,
+    conditional_requests: Arc<AtomicUsize>,
+}
+
+impl RegistryServer {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/plugins.json", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let conditional_requests = Arc::new(AtomicUsize::new(0));
+
+        let (req, cond) = (requests.clone(), conditional_requests.clone());
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                req.fetch_add(1, Ordering::SeqCst);
+                if Self::handle(stream) {
+                    cond.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        Self {
+            url,
+            requests,
+            conditional_requests,
+        }
+    }
+
+    /// Returns whether the request was answered with 304
+    fn handle(mut stream: TcpStream) -> bool {
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut not_modified = false;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                break;
+            }
+            let line = line.to_lowercase();
+            if line.starts_with("if-none-match:") && line.contains("\"v1\"") {
+                not_modified = true;
+            }
+        }
+
+        let response = if not_modified {
+            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string()
+        } else {
+            let body = json!([{ "name": "nonebot-plugin-status" }]).to_string();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        };
+        stream.write_all(response.as_bytes()).unwrap();
+        not_modified
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+
+    fn conditional_requests(&self) -> usize {
+        self.conditional_requests.load(Ordering::SeqCst)
+    }
+}
+
+#[tokio::test]
+async fn test_registry_cache_revalidation() {
+    let server = RegistryServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let cache_file = dir.path().join("plugins.json");
+    let client = Client::new();
+
+    // 无缓存时从远端获取，并记录 ETag
+    let data: Vec<Value> = RegistryFetcher::new(&client, &server.url, cache_file.clone())
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(server.requests(), 1);
+    let entry = CacheEntry::<Value>::read(&cache_file).unwrap();
+    assert_eq!(entry.source, server.url);
+    assert_eq!(entry.etag.as_deref(), Some("\"v1\""));
+
+    // TTL 内直接使用缓存
+    let data: Vec<Value> = RegistryFetcher::new(&client, &server.url, cache_file.clone())
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(server.requests(), 1);
+
+    // TTL 过期后发送条件请求，304 时沿用缓存
+    let data: Vec<Value> = RegistryFetcher::new(&client, &server.url, cache_file.clone())
+        .ttl(0)
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(server.requests(), 2);
+    assert_eq!(server.conditional_requests(), 1);
+
+    // 缓存无法写入时仍返回获取到的数据
+    let unwritable = dir.path().join("missing").join("plugins.json");
+    let data: Vec<Value> = RegistryFetcher::new(&client, &server.url, unwritable.clone())
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data.len(), 1);
+    assert!(!unwritable.exists());
+}
+
+#[tokio::test]
+async fn test_registry_cache_stale_fallback() {
+    let server = RegistryServer::start();
+    let dir = tempfile::tempdir().unwrap();
+    let cache_file = dir.path().join("plugins.json");
+    let client = Client::new();
+
+    // 写入一份过期缓存，来源指向一个不可用的地址
+    let unreachable = "http://127.0.0.1:1/plugins.json";
+    CacheEntry {
+        fetched_at: 0,
+        source: unreachable.to_string(),
+        etag: None,
+        last_modified: None,
+        data: vec![json!({ "name": "stale" })],
+    }
+    .write(&cache_file)
+    .unwrap();
+
+    let data: Vec<Value> = RegistryFetcher::new(&client, unreachable, cache_file.clone())
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data[0]["name"], "stale");
+
+    // 来源变更后旧缓存失效
+    let data: Vec<Value> = RegistryFetcher::new(&client, &server.url, cache_file.clone())
+        .fetch()
+        .await
+        .unwrap();
+    assert_eq!(data[0]["name"], "nonebot-plugin-status");
+    assert_eq!(server.conditional_requests(), 0);
+
+    // 无缓存且网络不可用时报错
+    let missing = dir.path().join("missing.json");
+    let result: anyhow::Result<Vec<Value>> = RegistryFetcher::new(&client, unreachable, missing)
+        .fetch()
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_registry_sources_priority() {
+    let dir = tempfile::tempdir().unwrap();
+    let mirror = dir.path().join("mirror.json");
+    let private = dir.path().join("private.json");
+    std::fs::write(
+        &mirror,
+        json!([
+            { "name": "nonebot-plugin-status", "version": "0.1.0" },
+            { "name": "nonebot-plugin-weather", "version": "0.2.0" }
+        ])
+        .to_string(),
+    )
+    .unwrap();
+    std::fs::write(
+        &private,
+        json!([{ "name": "nonebot-plugin-status", "version": "1.0.0" }]).to_string(),
+    )
+    .unwrap();
+
+    let source = |name: &str, path: &std::path::Path| RegistrySource {
+        name: name.to_string(),
+        plugins: Some(Url::from_file_path(path).unwrap().to_string()),
+        adapters: None,
+    };
+    let config = RegistryConfig {
+        use_official: false,
+        sources: vec![source("private", &private), source("mirror", &mirror)],
+        ..Default::default()
+    };
+
+    let plugins: std::collections::HashMap<String, Value> = registry::fetch_index(
+        &Client::new(),
+        &config,
+        RegistryIndex::Plugins,
+        false,
+        |p: &Value| p["name"].as_str().unwrap().to_string(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(plugins.len(), 2);
+    // 靠前的源优先
+    assert_eq!(plugins["nonebot-plugin-status"]["version"], "1.0.0");
+    assert_eq!(plugins["nonebot-plugin-weather"]["version"], "0.2.0");
+
+    // 没有源提供 adapters 时报错
+    let adapters: anyhow::Result<std::collections::HashMap<String, Value>> = registry::fetch_index(
+        &Client::new(),
+        &config,
+        RegistryIndex::Adapters,
+        false,
+        |a: &Value| a["name"].as_str().unwrap().to_string(),
+    )
+    .await;
+    assert!(adapters.is_err());
+}
+
+#[test]
+fn test_project_registry_config() {
+    let mut config: NbrConfig = toml::from_str(
+        r#"
+[registry]
+cache-ttl = 600
+
+[[registry.sources]]
+name = "mirror"
+plugins = "https://mirror.example.com/plugins.json"
+
+[publish]
+publish-url = "https://upload.pypi.org/legacy/"
+"#,
+    )
+    .unwrap();
+
+    let project = ProjectConfig::parse_from_str(
+        r#"
+[project]
+name = "bot"
+
+[tool.nbr.registry]
+use-official = false
+
+[[tool.nbr.registry.sources]]
+name = "private"
+plugins = "https://registry.example.com/plugins.json"
+adapters = "https://registry.example.com/adapters.json"
+
+[tool.nbr.publish]
+publish-url = "http://localhost:8080/"
+"#,
+    )
+    .unwrap();
+    config.merge_project(project);
+
+    assert_eq!(config.registry.cache_ttl, 600);
+    assert!(!config.registry.use_official);
+    assert_eq!(
+        config.publish.publish_url.as_deref(),
+        Some("http://localhost:8080/")
+    );
+    let plugin_sources = registry::resolve_sources(&config.registry, RegistryIndex::Plugins);
+    let names: Vec<&str> = plugin_sources.iter().map(|(n, _)| n.as_str()).collect();
+    assert_eq!(names, ["private", "mirror"]);
+    let adapter_sources = registry::resolve_sources(&config.registry, RegistryIndex::Adapters);
+    assert_eq!(adapter_sources.len(), 1);
+
+    config.registry.use_official = true;
+    let plugin_sources = registry::resolve_sources(&config.registry, RegistryIndex::Plugins);
+    assert_eq!(
+        plugin_sources.last().unwrap().1,
+        "https://registry.nonebot.dev/plugins.json"
+    );
+
+    // official 保留给 registry.nonebot.dev，同名的自定义源被忽略
+    config.registry.sources.push(RegistrySource {
+        name: registry::OFFICIAL_SOURCE.to_string(),
+        plugins: Some("https://mirror.example.com/plugins.json".to_string()),
+        adapters: None,
+    });
+    let plugin_sources = registry::resolve_sources(&config.registry, RegistryIndex::Plugins);
+    let official: Vec<&str> = plugin_sources
+        .iter()
+        .filter(|(n, _)| n == registry::OFFICIAL_SOURCE)
+        .map(|(_, url)| url.as_str())
+        .collect();
+    assert_eq!(official, ["https://registry.nonebot.dev/plugins.json"]);
+}
+
+#[test]
+fn test_malformed_project_config() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("pyproject.toml"),
+        r#"
+[project]
+name = "bot"
+
+[tool.nbr.registry]
+cache-ttl = "daily"
+"#,
+    )
+    .unwrap();
+    assert!(NbrConfig::load_with_project(dir.path()).is_err());
+    // 不使用 registry 的命令不受损坏的配置影响
+    let config = NbrConfig::load_with_project_or_default(dir.path());
+    assert_eq!(
+        config.registry.cache_ttl,
+        RegistryConfig::default().cache_ttl
+    );
+    assert!(nbr::cli::plugin::PluginManager::new(Some(dir.path().to_path_buf())).is_ok());
+    assert!(nbr::cli::adapter::AdapterManager::new(Some(dir.path().to_path_buf())).is_ok());
+}
+
+#[test]
+fn test_outdated_packages_from_registry() {
+    let package = |name: &str, version: &str| Package {
+        name: name.to_string(),
+        version: version.to_string(),
+        latest_version: None,
+        location: None,
+        requires: None,
+        requires_by: None,
+    };
+    let installed = vec![
+        package("nonebot-plugin-status", "0.9.0"),
+        package("nonebot_plugin_alconna", "0.54.0"),
+        package("nonebot-plugin-localstore", "0.7.4"),
+        package("nonebot-plugin-private", "1.0.0"),
+    ];
+    let latest_versions = HashMap::from([
+        ("nonebot_plugin_status".to_string(), "0.10.0".to_string()),
+        ("nonebot_plugin_alconna".to_string(), "0.54.0".to_string()),
+        (
+            "nonebot_plugin_localstore".to_string(),
+            "0.7.4rc1".to_string(),
+        ),
+    ]);
+
+    let outdated = registry::outdated_packages(installed, &latest_versions);
+    assert_eq!(outdated.len(), 1);
+    assert_eq!(outdated[0].name, "nonebot-plugin-status");
+    assert_eq!(outdated[0].latest_version.as_deref(), Some("0.10.0"));
+    assert!(outdated[0].is_outdated());
+}
@@ -1,4 +1,6 @@
 mod common;
+use nbr::cli::plugin::InstallOptions;
+use nbr::pyproject::PyProjectConfig;
 use nbr::uv;
 
 #[tokio::test]
@@ -14,3 +16,25 @@ async fn test_uv_operations_snapshot() {
         ".version" => "[VERSION]",
     });
 }
+
+#[tokio::test]
+async fn test_add_requirement_with_extras_and_specifier() {
+    let (_dir, project_path) = common::create_temp_project(false).await;
+
+    let options = InstallOptions::new("nonebot-plugin-x[foo] >= 1.2", false, false, None).unwrap();
+    let requirement = options.requirement();
+    // --frozen 只修改 pyproject.toml，不解析和安装依赖
+    uv::add(vec![&requirement])
+        .frozen(true)
+        .working_dir(&project_path)
+        .run()
+        .unwrap();
+
+    let pyproject = PyProjectConfig::parse(Some(&project_path)).unwrap();
+    assert!(
+        pyproject
+            .project
+            .dependencies
+            .contains(&"nonebot-plugin-x[foo]>=1.2".to_string())
+    );
+}